@@ -0,0 +1,146 @@
+use std::fmt;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{
+    to_binary, Addr, Api, BankMsg, Coin, CosmosMsg, QuerierWrapper, StdError, StdResult, Uint128,
+    WasmMsg,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
+
+/// A token the contract can hold and distribute
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetInfo {
+    /// A native bank denom
+    Native { denom: String },
+    /// A CW20 token contract
+    Cw20 { contract_addr: String },
+}
+
+impl AssetInfo {
+    pub fn native(denom: impl Into<String>) -> Self {
+        AssetInfo::Native {
+            denom: denom.into(),
+        }
+    }
+
+    pub fn cw20(contract_addr: impl Into<String>) -> Self {
+        AssetInfo::Cw20 {
+            contract_addr: contract_addr.into(),
+        }
+    }
+
+    /// Checks the contract address of a CW20 asset and normalizes it
+    pub fn validate(self, api: &dyn Api) -> StdResult<Self> {
+        match self {
+            AssetInfo::Native { denom } => Ok(AssetInfo::Native { denom }),
+            AssetInfo::Cw20 { contract_addr } => Ok(AssetInfo::Cw20 {
+                contract_addr: api.addr_validate(&contract_addr)?.into(),
+            }),
+        }
+    }
+
+    /// Storage key for per-asset maps. The kind prefix keeps a native denom
+    /// from ever colliding with a CW20 contract address.
+    pub fn key(&self) -> String {
+        match self {
+            AssetInfo::Native { denom } => format!("native:{}", denom),
+            AssetInfo::Cw20 { contract_addr } => format!("cw20:{}", contract_addr),
+        }
+    }
+
+    /// Inverse of `key`
+    pub fn from_key(key: &str) -> StdResult<Self> {
+        match key.split_once(':') {
+            Some(("native", denom)) => Ok(AssetInfo::native(denom)),
+            Some(("cw20", contract_addr)) => Ok(AssetInfo::cw20(contract_addr)),
+            _ => Err(StdError::parse_err("AssetInfo", format!("invalid key {}", key))),
+        }
+    }
+
+    /// Balance of this asset held by `address`
+    pub fn query_balance(&self, querier: &QuerierWrapper, address: &Addr) -> StdResult<Uint128> {
+        match self {
+            AssetInfo::Native { denom } => Ok(querier.query_balance(address, denom)?.amount),
+            AssetInfo::Cw20 { contract_addr } => {
+                let res: BalanceResponse = querier.query_wasm_smart(
+                    contract_addr,
+                    &Cw20QueryMsg::Balance {
+                        address: address.to_string(),
+                    },
+                )?;
+                Ok(res.balance)
+            }
+        }
+    }
+
+    /// Message sending `amount` of this asset from the contract to `recipient`
+    pub fn transfer_msg(&self, recipient: &str, amount: Uint128) -> StdResult<CosmosMsg> {
+        match self {
+            AssetInfo::Native { denom } => Ok(BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: vec![Coin {
+                    denom: denom.clone(),
+                    amount,
+                }],
+            }
+            .into()),
+            AssetInfo::Cw20 { contract_addr } => Ok(WasmMsg::Execute {
+                contract_addr: contract_addr.clone(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount,
+                })?,
+                funds: vec![],
+            }
+            .into()),
+        }
+    }
+}
+
+impl fmt::Display for AssetInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetInfo::Native { denom } => write!(f, "{}", denom),
+            AssetInfo::Cw20 { contract_addr } => write!(f, "{}", contract_addr),
+        }
+    }
+}
+
+/// An amount of some asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Asset {
+    pub info: AssetInfo,
+    pub amount: Uint128,
+}
+
+/// Messages sending `assets` from the contract to `recipient`. All native coins
+/// go out in a single bank send, sorted by denom as the bank module requires;
+/// every CW20 token needs its own transfer.
+pub fn transfer_msgs(recipient: &str, assets: &[Asset]) -> StdResult<Vec<CosmosMsg>> {
+    let mut coins: Vec<Coin> = vec![];
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for asset in assets {
+        match &asset.info {
+            AssetInfo::Native { denom } => coins.push(Coin {
+                denom: denom.clone(),
+                amount: asset.amount,
+            }),
+            AssetInfo::Cw20 { .. } => messages.push(asset.info.transfer_msg(recipient, asset.amount)?),
+        }
+    }
+    if !coins.is_empty() {
+        coins.sort_by(|a, b| a.denom.cmp(&b.denom));
+        messages.insert(
+            0,
+            BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: coins,
+            }
+            .into(),
+        );
+    }
+    Ok(messages)
+}
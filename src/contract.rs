@@ -1,31 +1,57 @@
+use std::cmp::Ordering;
+
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary,Addr,Uint128 , BankMsg, Coin, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{from_binary, to_binary,Addr, Api, Attribute, Binary, BlockInfo, CosmosMsg, Decimal, Deps, DepsMut, Empty, Env, Event, MessageInfo, Order, Response, StdError, StdResult, Storage, Uint128, Uint64};
 use cw2::set_contract_version;
+use cw20::Cw20ReceiveMsg;
+use cw_storage_plus::Bound;
+use cw_utils::Expiration;
 
 
+use crate::asset::{transfer_msgs, Asset, AssetInfo};
+use crate::cw4::{
+    query_all_members, query_members_after, AdminResponse, Cw4QueryMsg, HooksResponse, Member, MemberChangedHookMsg, MemberDiff, MemberListResponse,
+    MemberResponse, TotalWeightResponse,
+};
 use crate::error::ContractError;
-use crate::msg::{CountResponse, WhitelistResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{State, STATE};
+use crate::msg::{ApproversResponse, ClaimableResponse, CountResponse, WhitelistResponse, ExecuteMsg, DustRule, InstantiateMsg, IsMemberResponse, ListRolesResponse, ListSummary, ListsResponse, MemberCountResponse, MemberDetailsResponse, MigrateMsg, OwnerResponse, PauseStatusResponse, PendingDistributionInfo, PendingDistributionsResponse, QueuedActionInfo, QueuedActionsResponse, TimelockResponse, PendingApplication, PendingApplicationsResponse, PayoutMode, QueryMsg, ReceiveMsg, Role, RoleHolders, RoundResponse, ShareInfo, SharesResponse, SimulationResponse, Split};
+use crate::state::{legacy, ApprovalConfig, Application, PendingDistribution, GroupSource, ListInfo, MemberInfo, PauseInfo, Payout, QueuedAction, TimelockConfig, PendingOwner, Round, State, CLAIMABLE, DEFAULT_LIST, DEFAULT_WEIGHT, EXPIRES_AT_HEIGHT, EXPIRES_AT_TIME, HOOKS, LISTS, LIST_COUNT, LIST_IDS, LIST_TOTALS, MEMBERS, MEMBER_HISTORY, APPLICATIONS, APPROVAL_CONFIG, PAUSE, PENDING_DISTRIBUTIONS, PENDING_DISTRIBUTION_COUNT, PENDING_OWNER, QUEUE, QUEUE_COUNT, TIMELOCK, RESERVED, ROLES, OPEN_ROUNDS, ROUNDS, ROUND_COUNT, STATE, Totals};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:counter";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// number of recipients paid per transaction by distribution rounds
+const DEFAULT_BATCH_LIMIT: u32 = 50;
+const MAX_BATCH_LIMIT: u32 = 200;
+
+// page size of list queries
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+const MAX_LIST_NAME_LENGTH: usize = 64;
+
+const MAX_MEMO_LENGTH: usize = 256;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     let state = State {
         count: msg.count,
-        owner: info.sender.clone(),
-        whitelist: msg.whitelist,
+        owner: Some(info.sender.clone()),
     };
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     STATE.save(deps.storage, &state)?;
+    let list_id = create_list(deps.storage, DEFAULT_LIST)?;
+    for address in validate_addresses(deps.api, &msg.whitelist)? {
+        let member = new_member(&env, &info.sender, &MemberTerms::default());
+        save_member(deps.storage, list_id, &address, &member, env.block.height)?;
+    }
 
     Ok(Response::new()
         .add_attribute("method", "instantiate")
@@ -39,232 +65,3956 @@ pub fn execute(
     _env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    if stops_when_paused(&msg) && is_paused(deps.storage)? {
+        return Err(ContractError::Paused {});
+    }
+    if must_be_queued(deps.as_ref(), &_env.block, &msg)? {
+        return Err(ContractError::TimelockRequired {});
+    }
+    dispatch(deps, _env, info, msg, false)
+}
+
+/// Runs `msg`. `queued` is set for queued actions, which have already waited
+/// out the timelock.
+fn dispatch(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+    queued: bool,
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Increment {} => try_increment(deps),
+        ExecuteMsg::Pause {} => try_set_paused(deps, _env, info, true),
+        ExecuteMsg::Unpause {} => try_set_paused(deps, _env, info, false),
         ExecuteMsg::Reset { count } => try_reset(deps, info, count),
-        ExecuteMsg::Add { whitelist } => try_add_whitelist(deps, info, whitelist),
-        ExecuteMsg::Remove { whitelist } => try_remove_whitelist(deps, info, whitelist),
-        ExecuteMsg::Distribute { amount, denom } => try_token_distribute(deps.as_ref(), _env, info, amount, &denom),
+        ExecuteMsg::SetTimelock { timelock } => try_set_timelock(deps, info, timelock),
+        ExecuteMsg::QueueAction { msg } => try_queue_action(deps, _env, info, *msg),
+        ExecuteMsg::ExecuteQueued { id } => try_execute_queued(deps, _env, id),
+        ExecuteMsg::CancelQueued { id } => try_cancel_queued(deps, info, id),
+        ExecuteMsg::SetApprovers { approvers, required, threshold } => {
+            try_set_approvers(deps, info, approvers, required, threshold)
+        }
+        ExecuteMsg::ApproveDistribution { id } => try_approve_distribution(deps, _env, info, id),
+        ExecuteMsg::CancelDistribution { id } => try_cancel_distribution(deps, info, id),
+        ExecuteMsg::GrantRole { role, address } => try_grant_role(deps, info, role, address),
+        ExecuteMsg::RevokeRole { role, address } => try_revoke_role(deps, info, role, address),
+        ExecuteMsg::ProposeOwner { new_owner, expiry } => {
+            try_propose_owner(deps, _env, info, new_owner, expiry)
+        }
+        ExecuteMsg::AcceptOwnership {} => try_accept_ownership(deps, _env, info),
+        ExecuteMsg::CancelOwnershipProposal {} => try_cancel_ownership_proposal(deps, info),
+        ExecuteMsg::RenounceOwnership {} => try_renounce_ownership(deps, info),
+        ExecuteMsg::CreateList { name } => try_create_list(deps, info, name),
+        ExecuteMsg::RenameList { name, new_name } => try_rename_list(deps, info, name, new_name),
+        ExecuteMsg::DeleteList { name } => try_delete_list(deps, _env, info, name),
+        ExecuteMsg::AddHook { addr, list } => try_add_hook(deps, info, addr, list),
+        ExecuteMsg::RemoveHook { addr, list } => try_remove_hook(deps, info, addr, list),
+        ExecuteMsg::SetGroup { group, use_weights, list } => {
+            try_set_group(deps, info, group, use_weights.unwrap_or_default(), list)
+        }
+        ExecuteMsg::Add { whitelist, weight, strict, label, expires, list } => try_add_whitelist(
+            deps,
+            _env,
+            info,
+            list,
+            whitelist,
+            MemberTerms { weight, label, expires },
+            strict.unwrap_or_default(),
+        ),
+        ExecuteMsg::Remove { whitelist, strict, list } => {
+            try_remove_whitelist(deps, _env, info, list, whitelist, strict.unwrap_or_default())
+        }
+        ExecuteMsg::SetWhitelist { members, weight, strict, list } => try_set_whitelist(
+            deps,
+            _env,
+            info,
+            list,
+            members,
+            weight,
+            strict.unwrap_or_default(),
+        ),
+        ExecuteMsg::Apply { memo, list } => try_apply(deps, _env, info, memo, list),
+        ExecuteMsg::Approve { addresses, list } => try_approve(deps, _env, info, addresses, list),
+        ExecuteMsg::Reject { addresses, reason, list } => {
+            try_reject(deps, info, addresses, reason, list)
+        }
+        ExecuteMsg::Distribute { amount, asset, mode, split, at_height, list } => {
+            let terms = DistributionTerms {
+                mode: mode.unwrap_or_default(),
+                split: split.unwrap_or_default(),
+                at_height,
+                list,
+                skip_timelock: queued,
+                ..DistributionTerms::default()
+            };
+            try_token_distribute(deps, _env, info, amount, asset, terms)
+        }
+        ExecuteMsg::DistributeMany { assets, mode, split, list } => {
+            let terms = DistributionTerms {
+                mode: mode.unwrap_or_default(),
+                split: split.unwrap_or_default(),
+                list,
+                skip_timelock: queued,
+                ..DistributionTerms::default()
+            };
+            try_distribute_many(deps, _env, info, assets, terms)
+        }
+        ExecuteMsg::DistributeBalance { asset, mode, split, dust, list } => {
+            let terms = DistributionTerms {
+                mode: mode.unwrap_or_default(),
+                split: split.unwrap_or(Split::Even),
+                dust: dust.unwrap_or_default(),
+                list,
+                skip_timelock: queued,
+                ..DistributionTerms::default()
+            };
+            try_distribute_balance(deps, _env, info, asset, terms)
+        }
+        ExecuteMsg::DistributeFunds { mode, split, list } => {
+            let terms = DistributionTerms {
+                mode: mode.unwrap_or_default(),
+                split: split.unwrap_or(Split::Even),
+                list,
+                attached: true,
+                ..DistributionTerms::default()
+            };
+            try_distribute_funds(deps, _env, info, terms)
+        }
+        ExecuteMsg::ContinueDistribution { round_id, limit } => {
+            try_continue_distribution(deps, round_id, limit)
+        }
+        ExecuteMsg::CancelRound { round_id } => try_cancel_round(deps, info, round_id),
+        ExecuteMsg::Claim { asset } => try_claim(deps, info, asset),
+        ExecuteMsg::Receive(msg) => try_receive(info, msg),
     }
 }
 
-pub fn try_increment(deps: DepsMut) -> Result<Response, ContractError> {
-    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-        state.count += 1;
-        Ok(state)
-    })?;
-
-    Ok(Response::new().add_attribute("method", "try_increment"))
+/// Whether `msg` is refused while the contract is paused. Besides
+/// `Increment`, `Add`, `Remove` and `Distribute`, this covers the other ways
+/// to start or continue a payout (including approved and queued ones) and to
+/// change membership, since any of them could pay out to a compromised list.
+/// Everything else, such as `Claim` of already credited funds, keeps working.
+fn stops_when_paused(msg: &ExecuteMsg) -> bool {
+    matches!(
+        msg,
+        ExecuteMsg::Increment {}
+            | ExecuteMsg::Add { .. }
+            | ExecuteMsg::Remove { .. }
+            | ExecuteMsg::Distribute { .. }
+            | ExecuteMsg::SetWhitelist { .. }
+            | ExecuteMsg::SetGroup { .. }
+            | ExecuteMsg::Approve { .. }
+            | ExecuteMsg::DistributeMany { .. }
+            | ExecuteMsg::DistributeBalance { .. }
+            | ExecuteMsg::DistributeFunds { .. }
+            | ExecuteMsg::ContinueDistribution { .. }
+            | ExecuteMsg::ApproveDistribution { .. }
+            | ExecuteMsg::ExecuteQueued { .. }
+    )
 }
 
-pub fn try_reset(deps: DepsMut, info: MessageInfo, count: i32) -> Result<Response, ContractError> {
-    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-        if info.sender != state.owner {
-            return Err(ContractError::Unauthorized {});
+/// Whether the timelock requires `msg` to be queued rather than sent
+/// directly. Distributions are held against the threshold by
+/// `distribute_assets`, once their totals are known.
+fn must_be_queued(deps: Deps, block: &BlockInfo, msg: &ExecuteMsg) -> StdResult<bool> {
+    let timelock = match TIMELOCK.may_load(deps.storage)? {
+        Some(timelock) => timelock,
+        None => return Ok(false),
+    };
+    let threshold = timelock.bulk_remove_threshold as usize;
+    Ok(match msg {
+        ExecuteMsg::Reset { .. }
+        | ExecuteMsg::SetTimelock { .. }
+        | ExecuteMsg::SetApprovers { .. }
+        | ExecuteMsg::SetGroup { .. } => true,
+        ExecuteMsg::Remove { whitelist, .. } => whitelist.len() > threshold,
+        ExecuteMsg::SetWhitelist { members, list, .. } => {
+            dropped_members(deps, list.as_deref(), members)? > threshold
         }
-        state.count = count;
-        Ok(state)
-    })?;
-    Ok(Response::new().add_attribute("method", "reset"))
+        // cutting terms short removes members just as well, only later
+        ExecuteMsg::Add { whitelist, expires: Some(expires), list, .. } => {
+            shortened_members(deps, block, list.as_deref(), whitelist, expires)? > threshold
+        }
+        _ => false,
+    })
 }
 
+/// Number of stored members of `list` that a `SetWhitelist` to `members`
+/// would remove
+fn dropped_members(deps: Deps, list: Option<&str>, members: &[String]) -> StdResult<usize> {
+    let list_id = match LIST_IDS.may_load(deps.storage, list.unwrap_or(DEFAULT_LIST))? {
+        Some(list_id) => list_id,
+        // the handler reports the missing list
+        None => return Ok(0),
+    };
+    let members = validate_addresses(deps.api, members)?;
+    let mut dropped = 0;
+    for address in MEMBERS.prefix(list_id).keys(deps.storage, None, None, Order::Ascending) {
+        if !members.contains(&address?) {
+            dropped += 1;
+        }
+    }
+    Ok(dropped)
+}
 
-pub fn try_add_whitelist(deps: DepsMut, info: MessageInfo , addresses:  Vec<String>) -> Result<Response, ContractError> {
-    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-        if info.sender != state.owner {
-            return Err(ContractError::Unauthorized {});
+/// Number of current members of `list` among `addresses` whose term an `Add`
+/// expiring at `expires` would end sooner
+fn shortened_members(
+    deps: Deps,
+    block: &BlockInfo,
+    list: Option<&str>,
+    addresses: &[String],
+    expires: &Expiration,
+) -> StdResult<usize> {
+    let list_id = match LIST_IDS.may_load(deps.storage, list.unwrap_or(DEFAULT_LIST))? {
+        Some(list_id) => list_id,
+        None => return Ok(0),
+    };
+    let mut addresses = validate_addresses(deps.api, addresses)?;
+    addresses.sort();
+    addresses.dedup();
+    let mut shortened = 0;
+    for address in &addresses {
+        let member = MEMBERS.may_load(deps.storage, (list_id, address))?;
+        // terms of different kinds do not compare, and count as shortened
+        let shorter = |member: &MemberInfo| {
+            !matches!(expires.partial_cmp(&member.expires), Some(Ordering::Equal) | Some(Ordering::Greater))
+        };
+        if matches!(member, Some(member) if !member.expires.is_expired(block) && shorter(&member)) {
+            shortened += 1;
         }
-        state.whitelist.extend(addresses);
-        Ok(state)
-    })?;
+    }
+    Ok(shortened)
+}
 
-    Ok(Response::new().add_attribute("method", "try_add_whitelist"))
+/// The role needed to queue `msg`, or `None` for owner-only actions
+fn queue_role(msg: &ExecuteMsg) -> Result<Option<Role>, ContractError> {
+    match msg {
+        ExecuteMsg::Reset { .. } => Ok(Some(Role::CounterAdmin)),
+        ExecuteMsg::Add { .. } | ExecuteMsg::Remove { .. } | ExecuteMsg::SetWhitelist { .. } => {
+            Ok(Some(Role::WhitelistManager))
+        }
+        ExecuteMsg::Distribute { .. }
+        | ExecuteMsg::DistributeMany { .. }
+        | ExecuteMsg::DistributeBalance { .. } => Ok(Some(Role::Distributor)),
+        ExecuteMsg::SetTimelock { .. } | ExecuteMsg::SetApprovers { .. } | ExecuteMsg::SetGroup { .. } => Ok(None),
+        _ => Err(ContractError::NotQueueable {}),
+    }
 }
 
-pub fn try_remove_whitelist(deps: DepsMut, info: MessageInfo , addresses:  Vec<String>) -> Result<Response, ContractError> {
-    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
-        if info.sender != state.owner {
-            return Err(ContractError::Unauthorized {});
+pub fn try_set_approvers(
+    deps: DepsMut,
+    info: MessageInfo,
+    approvers: Vec<String>,
+    required: u32,
+    threshold: Uint128,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    let mut unique: Vec<Addr> = vec![];
+    for approver in validate_addresses(deps.api, &approvers)? {
+        if !unique.contains(&approver) {
+            unique.push(approver);
         }
-        state.whitelist.retain(|address| !addresses.contains(address));
-        Ok(state)
-    })?;
+    }
+    if unique.is_empty() {
+        // nobody is left to approve what is still pending
+        APPROVAL_CONFIG.remove(deps.storage);
+        let pending = PENDING_DISTRIBUTIONS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for id in &pending {
+            PENDING_DISTRIBUTIONS.remove(deps.storage, *id);
+        }
+        return Ok(Response::new()
+            .add_attribute("method", "try_set_approvers")
+            .add_attribute("required", "0")
+            .add_attribute("dropped_distributions", pending.len().to_string()));
+    }
+    if required == 0 || required as usize > unique.len() {
+        return Err(ContractError::InvalidApprovalCount {
+            required,
+            approvers: unique.len() as u32,
+        });
+    }
+    let config = ApprovalConfig { approvers: unique, required, threshold };
+    APPROVAL_CONFIG.save(deps.storage, &config)?;
 
-    Ok(Response::new().add_attribute("method", "try_remove_whitelist"))
+    Ok(Response::new()
+        .add_attribute("method", "try_set_approvers")
+        .add_attribute("required", required.to_string())
+        .add_attributes(list_attribute("approvers", &config.approvers)))
 }
 
-pub fn try_token_distribute(deps: Deps, env:Env, info: MessageInfo, amount: u128 , denom: &str) -> Result<Response, ContractError> { 
-    if info.sender != STATE.load(deps.storage)?.owner {
+/// Records the sender's approval of pending distribution `id`, and runs it
+/// as its proposer once the required count is reached. Approvers removed
+/// from the config since approving no longer count. An approver who already
+/// approved can still run a distribution that a lowered requirement let
+/// through.
+pub fn try_approve_distribution(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let config = APPROVAL_CONFIG.may_load(deps.storage)?;
+    let approvers = config.as_ref().map_or(&[][..], |config| &config.approvers[..]);
+    if !approvers.contains(&info.sender) {
         return Err(ContractError::Unauthorized {});
     }
+    let mut pending = PENDING_DISTRIBUTIONS
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::DistributionNotFound { id })?;
+    let approved = pending.approvals.contains(&info.sender);
+    if !approved {
+        pending.approvals.push(info.sender.clone());
+    }
+    let approvals = pending.approvals.iter().filter(|approver| approvers.contains(approver)).count();
+    let required = config.map_or(0, |config| config.required) as usize;
+    if approved && approvals < required {
+        return Err(ContractError::AlreadyApproved { id, approver: info.sender.into() });
+    }
+    let response = Response::new()
+        .add_attribute("method", "try_approve_distribution")
+        .add_attribute("pending_distribution_id", id.to_string())
+        .add_attribute("approver", info.sender)
+        .add_attribute("approvals", approvals.to_string());
+    if approvals < required {
+        PENDING_DISTRIBUTIONS.save(deps.storage, id, &pending)?;
+        return Ok(response);
+    }
 
-    let whitelist_response: WhitelistResponse = query_whitelist(deps)?;
-    let whitelist: Vec<String> = whitelist_response.whitelist;
-    let whitelist_len = whitelist.len() as u128;
-    let total_distribution_amount = amount * whitelist_len;
-    
-    
-    let contract_address: Addr = env.contract.address.clone();
-    // let denom = "token";
-    
-    // Query the balance of the contract address
-    let contract_balance: Coin = deps.querier.query_balance(contract_address, denom)?;
-    if contract_balance.amount < total_distribution_amount.into() {
-        return Err(ContractError::InsufficientError {});
+    let terms = DistributionTerms {
+        mode: pending.mode,
+        split: pending.split,
+        dust: pending.dust,
+        at_height: pending.at_height,
+        list: pending.list,
+        // a timelock set since the proposal applies to it as well
+        skip_timelock: pending.queued,
+        skip_approval: true,
+        ..DistributionTerms::default()
+    };
+    let mut assets = pending.assets;
+    if pending.sweep {
+        // whatever was paid or received since the proposal changed the balance
+        for asset in assets.iter_mut() {
+            asset.amount = query_free_balance(deps.as_ref(), &env, &asset.info)?;
+            if asset.amount.is_zero() {
+                return Err(ContractError::InsufficientError {});
+            }
+        }
     }
+    let distribution = distribute_assets(deps.branch(), &env, &pending.proposer, assets, terms)?;
+    PENDING_DISTRIBUTIONS.remove(deps.storage, id);
+    Ok(distribution
+        .add_attributes(response.attributes)
+        .add_attribute("executed", "true"))
+}
 
-    let mut messages: Vec<BankMsg> = vec![];
-    for address in whitelist {
-        let send_msg = BankMsg::Send {
-            to_address: address.clone(),
-            amount: vec![Coin {
-                denom: denom.to_string(),
-                amount: amount.into(),
-            }],
-        };
-        messages.push(send_msg);
+pub fn try_cancel_distribution(deps: DepsMut, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    if !PENDING_DISTRIBUTIONS.has(deps.storage, id) {
+        return Err(ContractError::DistributionNotFound { id });
     }
-    
-    Ok(Response::new().add_attribute("method", "try_token_distribute"))
+    PENDING_DISTRIBUTIONS.remove(deps.storage, id);
 
+    Ok(Response::new()
+        .add_attribute("method", "try_cancel_distribution")
+        .add_attribute("pending_distribution_id", id.to_string()))
 }
 
+pub fn try_set_timelock(
+    deps: DepsMut,
+    info: MessageInfo,
+    timelock: Option<TimelockConfig>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    match &timelock {
+        Some(timelock) => TIMELOCK.save(deps.storage, timelock)?,
+        None => TIMELOCK.remove(deps.storage),
+    }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetCount {} => to_binary(&query_count(deps)?),
-        QueryMsg::GetWhitelist {} => to_binary(&query_whitelist(deps)?),
+    Ok(Response::new()
+        .add_attribute("method", "try_set_timelock")
+        .add_attribute("delay", timelock.map_or(0, |timelock| timelock.delay).to_string()))
+}
+
+pub fn try_queue_action(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match queue_role(&msg)? {
+        Some(role) => assert_role(deps.storage, &info.sender, role)?,
+        None => assert_owner(deps.storage, &info.sender)?,
     }
+    let timelock = TIMELOCK.may_load(deps.storage)?.ok_or(ContractError::NoTimelock {})?;
+    let id = QUEUE_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    QUEUE_COUNT.save(deps.storage, &id)?;
+    let action = QueuedAction {
+        msg,
+        proposer: info.sender,
+        queued_at: env.block.time,
+        ready_at: env.block.time.plus_seconds(timelock.delay),
+    };
+    QUEUE.save(deps.storage, id, &action)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_queue_action")
+        .add_attribute("action_id", id.to_string())
+        .add_attribute("ready_at", action.ready_at.to_string()))
 }
 
-fn query_count(deps: Deps) -> StdResult<CountResponse> {
-    let state = STATE.load(deps.storage)?;
-    Ok(CountResponse { count: state.count })
+/// Runs queued action `id` as its proposer, whose roles are checked again
+/// now. A failing action stays queued.
+pub fn try_execute_queued(deps: DepsMut, env: Env, id: u64) -> Result<Response, ContractError> {
+    let action = QUEUE
+        .may_load(deps.storage, id)?
+        .ok_or(ContractError::QueuedActionNotFound { id })?;
+    if env.block.time < action.ready_at {
+        return Err(ContractError::TimelockNotExpired {
+            id,
+            ready_at: action.ready_at.to_string(),
+        });
+    }
+    QUEUE.remove(deps.storage, id);
+    let info = MessageInfo {
+        sender: action.proposer,
+        funds: vec![],
+    };
+    let response = dispatch(deps, env, info, action.msg, true)?;
+    Ok(response
+        .add_attribute("method", "try_execute_queued")
+        .add_attribute("action_id", id.to_string()))
 }
 
-fn query_whitelist(deps: Deps) -> StdResult<WhitelistResponse> {
-    let state = STATE.load(deps.storage)?;
-    Ok(WhitelistResponse { whitelist: state.whitelist })
+pub fn try_cancel_queued(deps: DepsMut, info: MessageInfo, id: u64) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    if !QUEUE.has(deps.storage, id) {
+        return Err(ContractError::QueuedActionNotFound { id });
+    }
+    QUEUE.remove(deps.storage, id);
+
+    Ok(Response::new()
+        .add_attribute("method", "try_cancel_queued")
+        .add_attribute("action_id", id.to_string()))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies_with_balance,mock_dependencies_with_balances, mock_env, mock_info};
-    use cosmwasm_std::{coins, from_binary, QuerierWrapper, Uint128};
+fn is_paused(storage: &dyn Storage) -> StdResult<bool> {
+    Ok(matches!(PAUSE.may_load(storage)?, Some(pause) if pause.paused))
+}
 
-    #[test]
-    fn proper_initialization_ops() {
-        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+pub fn try_set_paused(deps: DepsMut, env: Env, info: MessageInfo, paused: bool) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::Pauser)?;
+    match (paused, is_paused(deps.storage)?) {
+        (true, true) => return Err(ContractError::Paused {}),
+        (false, false) => return Err(ContractError::NotPaused {}),
+        _ => {}
+    }
+    let pause = PauseInfo {
+        paused,
+        set_by: info.sender,
+        set_at_height: env.block.height,
+        set_at_time: env.block.time,
+    };
+    PAUSE.save(deps.storage, &pause)?;
 
-        let msg = InstantiateMsg { count: 17 , whitelist: vec![] };
-        let info = mock_info("creator", &coins(1000, "earth"));
+    Ok(Response::new()
+        .add_attribute("method", if paused { "try_pause" } else { "try_unpause" })
+        .add_attribute("set_by", pause.set_by))
+}
 
-        // we can just call .unwrap() to assert this was a success
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+pub fn try_increment(deps: DepsMut) -> Result<Response, ContractError> {
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.count += 1;
+        Ok(state)
+    })?;
 
-        // it worked, let's query the state
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCount {}).unwrap();
-        let value: CountResponse = from_binary(&res).unwrap();
-        assert_eq!(17, value.count);
+    Ok(Response::new().add_attribute("method", "try_increment"))
+}
+
+pub fn try_reset(deps: DepsMut, info: MessageInfo, count: i32) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::CounterAdmin)?;
+    STATE.update(deps.storage, |mut state| -> Result<_, ContractError> {
+        state.count = count;
+        Ok(state)
+    })?;
+    Ok(Response::new().add_attribute("method", "reset"))
+}
+
+/// Checks that `sender` is the owner. A contract whose ownership was
+/// renounced has no owner, so this fails for everyone.
+fn assert_owner(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+    match STATE.load(storage)?.owner {
+        Some(owner) if owner == *sender => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
     }
+}
 
-    #[test]
-    fn increment() {
-        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+/// Checks that `sender` holds `role`, which the owner always does
+fn assert_role(storage: &dyn Storage, sender: &Addr, role: Role) -> Result<(), ContractError> {
+    if STATE.load(storage)?.owner.as_ref() == Some(sender) || ROLES.has(storage, (role.key(), sender)) {
+        return Ok(());
+    }
+    Err(ContractError::MissingRole { role: role.to_string() })
+}
 
-        let msg = InstantiateMsg { count: 17, whitelist: vec![]  };
-        let info = mock_info("creator", &coins(2, "token"));
-        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+pub fn try_grant_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: String,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    let holder = deps.api.addr_validate(&address)?;
+    if ROLES.has(deps.storage, (role.key(), &holder)) {
+        return Err(ContractError::RoleAlreadyGranted { role: role.to_string(), address });
+    }
+    ROLES.save(deps.storage, (role.key(), &holder), &Empty {})?;
 
-        // beneficiary can release it
-        let info = mock_info("anyone", &coins(2, "token"));
-        let msg = ExecuteMsg::Increment {};
-        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    Ok(Response::new()
+        .add_attribute("method", "try_grant_role")
+        .add_attribute("role", role.key())
+        .add_attribute("address", holder))
+}
 
-        // should increase counter by 1
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCount {}).unwrap();
-        let value: CountResponse = from_binary(&res).unwrap();
-        assert_eq!(18, value.count);
+pub fn try_revoke_role(
+    deps: DepsMut,
+    info: MessageInfo,
+    role: Role,
+    address: String,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    let holder = deps.api.addr_validate(&address)?;
+    if !ROLES.has(deps.storage, (role.key(), &holder)) {
+        return Err(ContractError::RoleNotGranted { role: role.to_string(), address });
     }
+    ROLES.remove(deps.storage, (role.key(), &holder));
 
-    #[test]
-    fn reset() {
-        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+    Ok(Response::new()
+        .add_attribute("method", "try_revoke_role")
+        .add_attribute("role", role.key())
+        .add_attribute("address", holder))
+}
 
-        let msg = InstantiateMsg { count: 17, whitelist: vec![]  };
-        let info = mock_info("creator", &coins(2, "token"));
-        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+pub fn try_propose_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_owner: String,
+    expiry: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    let address = deps.api.addr_validate(&new_owner)?;
+    let expiry = expiry.unwrap_or_default();
+    if expiry.is_expired(&env.block) {
+        return Err(ContractError::AlreadyExpired { expires: expiry.to_string() });
+    }
+    PENDING_OWNER.save(deps.storage, &PendingOwner { address: address.clone(), expiry })?;
 
-        // beneficiary can release it
-        let unauth_info = mock_info("anyone", &coins(2, "token"));
-        let msg = ExecuteMsg::Reset { count: 5 };
-        let res = execute(deps.as_mut(), mock_env(), unauth_info, msg);
-        match res {
-            Err(ContractError::Unauthorized {}) => {}
-            _ => panic!("Must return unauthorized error"),
-        }
+    Ok(Response::new()
+        .add_attribute("method", "try_propose_owner")
+        .add_attribute("pending_owner", address)
+        .add_attribute("expiry", expiry.to_string()))
+}
 
-        // only the original creator can reset the counter
-        let auth_info = mock_info("creator", &coins(2, "token"));
-        let msg = ExecuteMsg::Reset { count: 5 };
-        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+pub fn try_accept_ownership(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let pending = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingOwner {})?;
+    if info.sender != pending.address {
+        return Err(ContractError::Unauthorized {});
+    }
+    if pending.expiry.is_expired(&env.block) {
+        return Err(ContractError::OwnershipProposalExpired {});
+    }
+    PENDING_OWNER.remove(deps.storage);
+    STATE.update(deps.storage, |mut state| -> StdResult<_> {
+        state.owner = Some(pending.address.clone());
+        Ok(state)
+    })?;
 
-        // should now be 5
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCount {}).unwrap();
-        let value: CountResponse = from_binary(&res).unwrap();
-        assert_eq!(5, value.count);
+    Ok(Response::new()
+        .add_attribute("method", "try_accept_ownership")
+        .add_attribute("owner", pending.address))
+}
+
+pub fn try_cancel_ownership_proposal(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    if PENDING_OWNER.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoPendingOwner {});
     }
+    PENDING_OWNER.remove(deps.storage);
 
-    
-    #[test]
-    fn add_whitelist() {
-        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+    Ok(Response::new().add_attribute("method", "try_cancel_ownership_proposal"))
+}
 
-        let msg = InstantiateMsg { count: 17, whitelist: vec![]  };
-        let info = mock_info("creator", &coins(2, "token"));
-        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+pub fn try_renounce_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    PENDING_OWNER.remove(deps.storage);
+    STATE.update(deps.storage, |mut state| -> StdResult<_> {
+        state.owner = None;
+        Ok(state)
+    })?;
 
-        let addresses_to_add = vec!["address1".to_string(), "address2".to_string()];
-        let add_result = try_add_whitelist(deps.as_mut(), info, addresses_to_add.clone());
+    Ok(Response::new().add_attribute("method", "try_renounce_ownership"))
+}
 
-        assert!(add_result.is_ok());
-        // // beneficiary can release it
-        // let info = mock_info("anyone", &coins(2, "token"));
-        // let msg = ExecuteMsg::Increment {};
-        // let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-        // // should increase counter by 1
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetWhitelist {}).unwrap();
-        let value: WhitelistResponse = from_binary(&res).unwrap();
-        assert_eq!(addresses_to_add, value.whitelist);
-    }
+pub fn try_create_list(deps: DepsMut, info: MessageInfo, name: String) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    let id = create_list(deps.storage, &name)?;
 
-    
-    #[test]
-    fn remove_whitelist() {
-        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+    Ok(Response::new()
+        .add_attribute("method", "try_create_list")
+        .add_attribute("list", name)
+        .add_attribute("list_id", id.to_string()))
+}
 
-        let msg = InstantiateMsg { count: 17, whitelist: vec!["address1".to_string(), "address2".to_string()]  };
-        let info = mock_info("creator", &coins(2, "token"));
-        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+pub fn try_rename_list(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    new_name: String,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    if name == DEFAULT_LIST {
+        return Err(ContractError::DefaultList {});
+    }
+    let id = load_list_id(deps.storage, Some(&name))?;
+    check_list_name(deps.storage, &new_name)?;
+    LIST_IDS.remove(deps.storage, &name);
+    LIST_IDS.save(deps.storage, &new_name, &id)?;
+    let mut list = LISTS.load(deps.storage, id)?;
+    list.name = new_name.clone();
+    LISTS.save(deps.storage, id, &list)?;
 
-        let addresses_to_remove = vec!["address1".to_string()];
-        let remove_result = try_remove_whitelist(deps.as_mut(), info, addresses_to_remove.clone());
+    Ok(Response::new()
+        .add_attribute("method", "try_rename_list")
+        .add_attribute("list", name)
+        .add_attribute("new_name", new_name))
+}
 
-        assert!(remove_result.is_ok());
-        // // beneficiary can release it
-        // let info = mock_info("anyone", &coins(2, "token"));
-        // let msg = ExecuteMsg::Increment {};
-        // let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+/// Deletes an empty list that no open round pays, along with its hooks,
+/// applications, member history and totals. Its membership snapshots are
+/// kept, but can no longer be queried by name.
+pub fn try_delete_list(deps: DepsMut, env: Env, info: MessageInfo, name: String) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    if name == DEFAULT_LIST {
+        return Err(ContractError::DefaultList {});
+    }
+    let id = load_list_id(deps.storage, Some(&name))?;
+    if LIST_TOTALS.may_load(deps.storage, id)?.unwrap_or_default().members > 0 {
+        return Err(ContractError::ListNotEmpty { name });
+    }
+    if OPEN_ROUNDS.prefix(id).keys(deps.storage, None, None, Order::Ascending).next().is_some() {
+        return Err(ContractError::ListInUse { name });
+    }
+    LIST_IDS.remove(deps.storage, &name);
+    LISTS.remove(deps.storage, id);
 
-        // // should increase counter by 1
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetWhitelist {}).unwrap();
-        let value: WhitelistResponse = from_binary(&res).unwrap();
-        assert_eq!(vec!["address2".to_string()], value.whitelist);
+    // list ids are never handed out again, so nothing else would clear these
+    for map in [HOOKS, MEMBER_HISTORY] {
+        let addresses = map.prefix(id).keys(deps.storage, None, None, Order::Ascending).collect::<StdResult<Vec<_>>>()?;
+        for address in addresses {
+            map.remove(deps.storage, (id, &address));
+        }
+    }
+    let applicants = APPLICATIONS
+        .prefix(id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for applicant in applicants {
+        APPLICATIONS.remove(deps.storage, (id, &applicant));
+    }
+    for index in [EXPIRES_AT_HEIGHT, EXPIRES_AT_TIME] {
+        let points = index.prefix(id).keys(deps.storage, None, None, Order::Ascending).collect::<StdResult<Vec<_>>>()?;
+        for at in points {
+            index.remove(deps.storage, (id, at), env.block.height)?;
+        }
+    }
+    LIST_TOTALS.remove(deps.storage, id, env.block.height)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_delete_list")
+        .add_attribute("list", name))
+}
+
+/// Points list `list` at the cw4 group `group`, or back at its own members.
+/// The group is asked for its total weight first, so an address that is not
+/// a cw4 group is rejected here rather than at the next distribution.
+pub fn try_set_group(
+    deps: DepsMut,
+    info: MessageInfo,
+    group: Option<String>,
+    use_weights: bool,
+    list: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    let list_id = load_list_id(deps.storage, list.as_deref())?;
+    let group = match group {
+        Some(group) => {
+            let addr = deps.api.addr_validate(&group)?;
+            let _: TotalWeightResponse = deps
+                .querier
+                .query_wasm_smart(&addr, &Cw4QueryMsg::TotalWeight {})?;
+            Some(GroupSource { addr, use_weights })
+        }
+        None => None,
+    };
+    let mut list_info = LISTS.load(deps.storage, list_id)?;
+    list_info.group = group.clone();
+    LISTS.save(deps.storage, list_id, &list_info)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_set_group")
+        .add_attribute("list", list_info.name)
+        .add_attribute("group", group.map_or_else(|| "none".to_string(), |group| group.addr.into())))
+}
+
+/// What `Add` sets on the entries it creates. Unset fields fall back to the
+/// default weight, no label and no expiration.
+#[derive(Default)]
+pub struct MemberTerms {
+    pub weight: Option<u64>,
+    pub label: Option<String>,
+    pub expires: Option<Expiration>,
+}
+
+/// Adds `addresses` to list `list`. Addresses that are already members, or
+/// listed twice, are an error when `strict` is set and skipped otherwise. A
+/// skipped member still takes whatever `terms` are given, so re-adding can
+/// change a weight or extend a term. Expired entries are replaced.
+pub fn try_add_whitelist(deps: DepsMut, env: Env, info: MessageInfo, list: Option<String>, addresses:  Vec<String>, terms: MemberTerms, strict: bool) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::WhitelistManager)?;
+    let list_id = load_list_id(deps.storage, list.as_deref())?;
+    if let Some(expires) = terms.expires {
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::AlreadyExpired { expires: expires.to_string() });
+        }
+    }
+    let addresses = validate_addresses(deps.api, &addresses)?;
+    let (added, mut skipped, repeated) =
+        partition_members(addresses, |address| is_member(deps.storage, &env.block, list_id, address))?;
+    skipped.extend(repeated);
+    if let (true, Some(address)) = (strict, skipped.first()) {
+        return Err(ContractError::DuplicateMember { address: address.into() });
+    }
+    let mut diffs: Vec<MemberDiff> = vec![];
+    for address in &added {
+        let member = new_member(&env, &info.sender, &terms);
+        save_member(deps.storage, list_id, address, &member, env.block.height)?;
+        diffs.push(MemberDiff::new(address, None, Some(member.weight)));
+    }
+    for address in &skipped {
+        let mut member = MEMBERS.load(deps.storage, (list_id, address))?;
+        let old_weight = member.weight;
+        if let Some(weight) = terms.weight {
+            member.weight = weight;
+        }
+        if terms.label.is_some() {
+            member.label = terms.label.clone();
+        }
+        if let Some(expires) = terms.expires {
+            member.expires = expires;
+        }
+        save_member(deps.storage, list_id, address, &member, env.block.height)?;
+        if member.weight != old_weight {
+            diffs.push(MemberDiff::new(address, Some(old_weight), Some(member.weight)));
+        }
+    }
+    let hooks = hook_messages(deps.storage, list_id, diffs)?;
+
+    Ok(Response::new()
+        .add_messages(hooks)
+        .add_attribute("method", "try_add_whitelist")
+        .add_attribute("list", list.as_deref().unwrap_or(DEFAULT_LIST))
+        .add_attributes(list_attribute("added", &added))
+        .add_attributes(list_attribute("skipped", &skipped)))
+}
+
+/// Removes `addresses` from list `list`. Addresses that are not members, or
+/// listed twice, are an error when `strict` is set and skipped otherwise.
+pub fn try_remove_whitelist(deps: DepsMut, env: Env, info: MessageInfo, list: Option<String>, addresses:  Vec<String>, strict: bool) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::WhitelistManager)?;
+    let list_id = load_list_id(deps.storage, list.as_deref())?;
+    // expired entries are still stored, and removing them is how they are cleaned up
+    let addresses = validate_addresses(deps.api, &addresses)?;
+    let (mut skipped, removed, repeated) =
+        partition_members(addresses, |address| Ok(MEMBERS.may_load(deps.storage, (list_id, address))?.is_some()))?;
+    skipped.extend(repeated);
+    if let (true, Some(address)) = (strict, skipped.first()) {
+        return Err(ContractError::NotAMember { address: address.into() });
+    }
+    let mut diffs: Vec<MemberDiff> = vec![];
+    for address in &removed {
+        let member = remove_member(deps.storage, list_id, address, env.block.height)?;
+        // an expired member had already left as far as hooks are concerned
+        if let Some(member) = member.filter(|member| !member.expires.is_expired(&env.block)) {
+            diffs.push(MemberDiff::new(address, Some(member.weight), None));
+        }
+    }
+    let hooks = hook_messages(deps.storage, list_id, diffs)?;
+
+    Ok(Response::new()
+        .add_messages(hooks)
+        .add_attribute("method", "try_remove_whitelist")
+        .add_attribute("list", list.as_deref().unwrap_or(DEFAULT_LIST))
+        .add_attributes(list_attribute("removed", &removed))
+        .add_attributes(list_attribute("skipped", &skipped)))
+}
+
+/// Makes `addresses` the members of list `list`. Every change is checked
+/// before the first one is stored, and each added or removed member is
+/// reported in its own event.
+pub fn try_set_whitelist(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    list: Option<String>,
+    addresses: Vec<String>,
+    weight: Option<u64>,
+    strict: bool,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::WhitelistManager)?;
+    let list_id = load_list_id(deps.storage, list.as_deref())?;
+    let list_name = list.as_deref().unwrap_or(DEFAULT_LIST);
+    let addresses = validate_addresses(deps.api, &addresses)?;
+    let (added, kept, skipped) =
+        partition_members(addresses, |address| is_member(deps.storage, &env.block, list_id, address))?;
+    if let (true, Some(address)) = (strict, skipped.first()) {
+        return Err(ContractError::DuplicateMember { address: address.into() });
+    }
+    // expired entries of addresses that are added again are replaced instead
+    let removed = MEMBERS
+        .prefix(list_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .filter(|address| !matches!(address, Ok(address) if added.contains(address) || kept.contains(address)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut diffs: Vec<MemberDiff> = vec![];
+    let mut events: Vec<Event> = vec![];
+    for address in &removed {
+        let member = remove_member(deps.storage, list_id, address, env.block.height)?;
+        if let Some(member) = member.filter(|member| !member.expires.is_expired(&env.block)) {
+            diffs.push(MemberDiff::new(address, Some(member.weight), None));
+        }
+        events.push(
+            Event::new("whitelist_removed")
+                .add_attribute("list", list_name)
+                .add_attribute("address", address),
+        );
+    }
+    let terms = MemberTerms { weight, ..MemberTerms::default() };
+    for address in &added {
+        let member = new_member(&env, &info.sender, &terms);
+        save_member(deps.storage, list_id, address, &member, env.block.height)?;
+        diffs.push(MemberDiff::new(address, None, Some(member.weight)));
+        events.push(
+            Event::new("whitelist_added")
+                .add_attribute("list", list_name)
+                .add_attribute("address", address)
+                .add_attribute("weight", member.weight.to_string()),
+        );
+    }
+    for address in &kept {
+        let mut member = MEMBERS.load(deps.storage, (list_id, address))?;
+        match weight {
+            Some(weight) if weight != member.weight => {
+                diffs.push(MemberDiff::new(address, Some(member.weight), Some(weight)));
+                member.weight = weight;
+                save_member(deps.storage, list_id, address, &member, env.block.height)?;
+            }
+            _ => {}
+        }
+    }
+    let hooks = hook_messages(deps.storage, list_id, diffs)?;
+
+    Ok(Response::new()
+        .add_messages(hooks)
+        .add_events(events)
+        .add_attribute("method", "try_set_whitelist")
+        .add_attribute("list", list_name)
+        .add_attributes(list_attribute("skipped", &skipped)))
+}
+
+pub fn try_apply(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    memo: Option<String>,
+    list: Option<String>,
+) -> Result<Response, ContractError> {
+    let list_id = load_list_id(deps.storage, list.as_deref())?;
+    if matches!(&memo, Some(memo) if memo.len() > MAX_MEMO_LENGTH) {
+        return Err(ContractError::MemoTooLong { max: MAX_MEMO_LENGTH });
+    }
+    if is_member(deps.storage, &env.block, list_id, &info.sender)? {
+        return Err(ContractError::DuplicateMember { address: info.sender.into() });
+    }
+    if APPLICATIONS.has(deps.storage, (list_id, &info.sender)) {
+        return Err(ContractError::AlreadyApplied { address: info.sender.into() });
+    }
+    let application = Application {
+        memo,
+        applied_at_height: env.block.height,
+        applied_at_time: env.block.time,
+    };
+    APPLICATIONS.save(deps.storage, (list_id, &info.sender), &application)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_apply")
+        .add_attribute("list", list.as_deref().unwrap_or(DEFAULT_LIST))
+        .add_attribute("applicant", info.sender))
+}
+
+/// Validates `addresses` and checks that each of them has a pending
+/// application to list `list_id`. Repeated addresses are dropped.
+fn load_applicants(deps: Deps, list_id: u64, addresses: &[String]) -> Result<Vec<Addr>, ContractError> {
+    let mut applicants: Vec<Addr> = vec![];
+    for address in validate_addresses(deps.api, addresses)? {
+        if !APPLICATIONS.has(deps.storage, (list_id, &address)) {
+            return Err(ContractError::NoApplication { address: address.into() });
+        }
+        if !applicants.contains(&address) {
+            applicants.push(address);
+        }
+    }
+    Ok(applicants)
+}
+
+pub fn try_approve(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addresses: Vec<String>,
+    list: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::WhitelistManager)?;
+    let list_id = load_list_id(deps.storage, list.as_deref())?;
+    let applicants = load_applicants(deps.as_ref(), list_id, &addresses)?;
+    let (approved, skipped, _) =
+        partition_members(applicants, |address| is_member(deps.storage, &env.block, list_id, address))?;
+    let mut diffs: Vec<MemberDiff> = vec![];
+    for address in &approved {
+        let member = new_member(&env, &info.sender, &MemberTerms::default());
+        save_member(deps.storage, list_id, address, &member, env.block.height)?;
+        diffs.push(MemberDiff::new(address, None, Some(member.weight)));
+    }
+    for address in approved.iter().chain(&skipped) {
+        APPLICATIONS.remove(deps.storage, (list_id, address));
+    }
+    let hooks = hook_messages(deps.storage, list_id, diffs)?;
+
+    Ok(Response::new()
+        .add_messages(hooks)
+        .add_attribute("method", "try_approve")
+        .add_attribute("list", list.as_deref().unwrap_or(DEFAULT_LIST))
+        .add_attributes(list_attribute("approved", &approved))
+        .add_attributes(list_attribute("skipped", &skipped)))
+}
+
+pub fn try_reject(
+    deps: DepsMut,
+    info: MessageInfo,
+    addresses: Vec<String>,
+    reason: Option<String>,
+    list: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, &info.sender, Role::WhitelistManager)?;
+    let list_id = load_list_id(deps.storage, list.as_deref())?;
+    if matches!(&reason, Some(reason) if reason.len() > MAX_MEMO_LENGTH) {
+        return Err(ContractError::MemoTooLong { max: MAX_MEMO_LENGTH });
+    }
+    let rejected = load_applicants(deps.as_ref(), list_id, &addresses)?;
+    for address in &rejected {
+        APPLICATIONS.remove(deps.storage, (list_id, address));
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "try_reject")
+        .add_attribute("list", list.as_deref().unwrap_or(DEFAULT_LIST))
+        .add_attributes(list_attribute("rejected", &rejected))
+        .add_attributes(reason.filter(|reason| !reason.is_empty()).map(|reason| ("reason", reason))))
+}
+
+/// Registers `addr` to be sent a cw4 `MemberChangedHookMsg` whenever list
+/// `list` changes
+pub fn try_add_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    addr: String,
+    list: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    let list_id = load_list_id(deps.storage, list.as_deref())?;
+    let hook = deps.api.addr_validate(&addr)?;
+    if HOOKS.has(deps.storage, (list_id, &hook)) {
+        return Err(ContractError::HookAlreadyRegistered { hook: addr });
+    }
+    HOOKS.save(deps.storage, (list_id, &hook), &Empty {})?;
+
+    Ok(Response::new()
+        .add_attribute("method", "try_add_hook")
+        .add_attribute("hook", hook))
+}
+
+pub fn try_remove_hook(
+    deps: DepsMut,
+    info: MessageInfo,
+    addr: String,
+    list: Option<String>,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    let list_id = load_list_id(deps.storage, list.as_deref())?;
+    let hook = deps.api.addr_validate(&addr)?;
+    if !HOOKS.has(deps.storage, (list_id, &hook)) {
+        return Err(ContractError::HookNotRegistered { hook: addr });
+    }
+    HOOKS.remove(deps.storage, (list_id, &hook));
+
+    Ok(Response::new()
+        .add_attribute("method", "try_remove_hook")
+        .add_attribute("hook", hook))
+}
+
+/// Messages telling every hook of list `list_id` about `diffs`
+fn hook_messages(storage: &dyn Storage, list_id: u64, diffs: Vec<MemberDiff>) -> StdResult<Vec<CosmosMsg>> {
+    if diffs.is_empty() {
+        return Ok(vec![]);
+    }
+    HOOKS
+        .prefix(list_id)
+        .keys(storage, None, None, Order::Ascending)
+        .map(|hook| MemberChangedHookMsg::new(diffs.clone()).into_cosmos_msg(hook?))
+        .collect()
+}
+
+/// How a distribution pays out its assets and which membership it pays
+#[derive(Default)]
+pub struct DistributionTerms {
+    pub mode: PayoutMode,
+    pub split: Split,
+    pub dust: DustRule,
+    /// Pay the whitelist as it stood at this height instead of the current one
+    pub at_height: Option<u64>,
+    /// The list to pay, `DEFAULT_LIST` when unset
+    pub list: Option<String>,
+    /// Pay even above the timelock threshold
+    pub skip_timelock: bool,
+    /// Pay even above the approval threshold
+    pub skip_approval: bool,
+    /// The assets were sent along with the call, so the distribution cannot
+    /// be held back and is refused where it would have to wait
+    pub attached: bool,
+    /// The amounts are the free balance, to be read again if the
+    /// distribution has to wait for approval
+    pub sweep: bool,
+}
+
+pub fn try_token_distribute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    asset: AssetInfo,
+    terms: DistributionTerms,
+) -> Result<Response, ContractError> {
+    let assets = vec![Asset { info: asset, amount }];
+    let response = distribute_assets(deps, &env, &info.sender, assets, terms)?;
+    Ok(response.add_attribute("method", "try_token_distribute"))
+}
+
+pub fn try_distribute_many(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    assets: Vec<Asset>,
+    terms: DistributionTerms,
+) -> Result<Response, ContractError> {
+    let response = distribute_assets(deps, &env, &info.sender, assets, terms)?;
+    Ok(response.add_attribute("method", "try_distribute_many"))
+}
+
+/// Distributes the whole free balance of `asset`, so the operator does not have
+/// to work out an amount that may be stale by the time it executes.
+pub fn try_distribute_balance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset: AssetInfo,
+    terms: DistributionTerms,
+) -> Result<Response, ContractError> {
+    if terms.split == Split::Fixed {
+        return Err(ContractError::InvalidSplit { split: terms.split.to_string() });
+    }
+    if terms.dust == DustRule::Sender {
+        return Err(ContractError::InvalidDustRule { rule: terms.dust.to_string() });
+    }
+    let asset = asset.validate(deps.api)?;
+    let amount = query_free_balance(deps.as_ref(), &env, &asset)?;
+    if amount.is_zero() {
+        return Err(ContractError::InsufficientError {});
+    }
+
+    let assets = vec![Asset { info: asset, amount }];
+    let terms = DistributionTerms { sweep: true, ..terms };
+    let response = distribute_assets(deps, &env, &info.sender, assets, terms)?;
+    Ok(response.add_attribute("method", "try_distribute_balance"))
+}
+
+/// Splits the coins attached to the call across the whitelist, so the contract
+/// does not need to be funded up front. The rounding remainder goes straight
+/// back to the sender.
+pub fn try_distribute_funds(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    terms: DistributionTerms,
+) -> Result<Response, ContractError> {
+    if terms.split == Split::Fixed {
+        return Err(ContractError::InvalidSplit { split: terms.split.to_string() });
+    }
+    let coin = match info.funds.as_slice() {
+        [] => return Err(ContractError::NoFunds {}),
+        [coin] => coin.clone(),
+        _ => return Err(ContractError::MixedFunds {}),
+    };
+
+    let assets = vec![Asset {
+        info: AssetInfo::native(coin.denom),
+        amount: coin.amount,
+    }];
+    let terms = DistributionTerms { dust: DustRule::Sender, ..terms };
+    let response = distribute_assets(deps, &env, &info.sender, assets, terms)?;
+    Ok(response.add_attribute("method", "try_distribute_funds"))
+}
+
+/// Starts a round distributing every asset in `assets` across the whitelist,
+/// once the contract can cover all of them, and pays its first page.
+fn distribute_assets(
+    deps: DepsMut,
+    env: &Env,
+    sender: &Addr,
+    assets: Vec<Asset>,
+    terms: DistributionTerms,
+) -> Result<Response, ContractError> {
+    assert_role(deps.storage, sender, Role::Distributor)?;
+    // only attached funds are the sender's to get back
+    if terms.dust == DustRule::Sender && !terms.attached {
+        return Err(ContractError::InvalidDustRule { rule: terms.dust.to_string() });
+    }
+    if assets.is_empty() {
+        return Err(ContractError::NoAssets {});
+    }
+    let assets = assets
+        .into_iter()
+        .map(|asset| {
+            Ok(Asset {
+                info: asset.info.validate(deps.api)?,
+                amount: asset.amount,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    for (i, asset) in assets.iter().enumerate() {
+        if assets[..i].iter().any(|other| other.info == asset.info) {
+            return Err(ContractError::DuplicateAsset {
+                asset: asset.info.to_string(),
+            });
+        }
+    }
+
+    let round = new_round(deps.as_ref(), env, sender, assets, &terms)?;
+    let DistributionTerms { mode, split, dust, at_height, list, skip_timelock, skip_approval, attached, sweep } = terms;
+    if !skip_timelock {
+        if let Some(timelock) = TIMELOCK.may_load(deps.storage)? {
+            if round.total.iter().any(|asset| asset.amount > timelock.distribute_threshold) {
+                if attached {
+                    return Err(ContractError::AttachedFundsAboveTimelock {});
+                }
+                return Err(ContractError::TimelockRequired {});
+            }
+        }
+    }
+    if !skip_approval {
+        if let Some(config) = APPROVAL_CONFIG.may_load(deps.storage)? {
+            if round.total.iter().any(|asset| asset.amount > config.threshold) {
+                if attached {
+                    return Err(ContractError::AttachedFundsAboveApproval {});
+                }
+                let pending = PendingDistribution {
+                    proposer: sender.clone(),
+                    assets: round.assets,
+                    mode,
+                    split,
+                    dust,
+                    at_height,
+                    list,
+                    approvals: vec![],
+                    queued: skip_timelock,
+                    sweep,
+                };
+                let id = PENDING_DISTRIBUTION_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+                PENDING_DISTRIBUTION_COUNT.save(deps.storage, &id)?;
+                PENDING_DISTRIBUTIONS.save(deps.storage, id, &pending)?;
+                return Ok(Response::new()
+                    .add_attribute("pending_distribution_id", id.to_string())
+                    .add_attribute("approvals_required", config.required.to_string()));
+            }
+        }
+    }
+
+    // Every asset must be covered before anything is paid. Funds already owed
+    // to claimants or open rounds are not available for a new distribution.
+    for asset in &round.total {
+        let free_balance = query_free_balance(deps.as_ref(), env, &asset.info)?;
+        if free_balance < asset.amount {
+            return Err(ContractError::InsufficientError {});
+        }
+    }
+
+    let recipients = round.recipients;
+    let round_id = create_round(deps.storage, &round)?;
+    let response = pay_round_page(deps, round_id, DEFAULT_BATCH_LIMIT)?;
+
+    Ok(response
+        .add_attribute("mode", mode.to_string())
+        .add_attribute("split", split.to_string())
+        .add_attribute("dust_rule", dust.to_string())
+        .add_attribute("list", list.as_deref().unwrap_or(DEFAULT_LIST))
+        .add_attribute("recipients", recipients.to_string())
+        .add_attributes(at_height.map(|height| ("at_height", height.to_string()))))
+}
+
+/// A round paying `assets` to the membership named by `terms`, not stored
+/// yet. The membership is fixed at `at_height`, or else as it stands once
+/// the current transaction is done; only its totals are read here.
+fn new_round(
+    deps: Deps,
+    env: &Env,
+    created_by: &Addr,
+    assets: Vec<Asset>,
+    terms: &DistributionTerms,
+) -> Result<Round, ContractError> {
+    let list_id = load_list_id(deps.storage, terms.list.as_deref())?;
+    let list = LISTS.load(deps.storage, list_id)?;
+    let (height, block) = match (&list.group, terms.at_height) {
+        (Some(_), Some(_)) => return Err(ContractError::GroupAtHeight { name: list.name }),
+        (None, Some(height)) if height > env.block.height => {
+            return Err(ContractError::FutureHeight { height })
+        }
+        (_, Some(height)) => (height, BlockInfo { height, ..env.block.clone() }),
+        (_, None) => (env.block.height + 1, env.block.clone()),
+    };
+    let (recipients, total_weight) = match &list.group {
+        Some(_) => {
+            let members = distribution_members(deps, &env.block, list_id, None)?;
+            (members.len() as u64, sum_weights(&members)?)
+        }
+        None => active_totals_at_height(deps.storage, &block, list_id, height)?,
+    };
+    let unsplittable = match terms.split {
+        Split::Fixed => false,
+        Split::Even => recipients == 0,
+        Split::Weighted => total_weight == 0,
+    };
+    if unsplittable {
+        return Err(ContractError::ZeroTotalWeight {});
+    }
+
+    let total = assets
+        .iter()
+        .map(|asset| {
+            Ok(Asset {
+                info: asset.info.clone(),
+                amount: match terms.split {
+                    Split::Fixed => asset.amount.checked_mul(Uint128::from(recipients))?,
+                    Split::Even | Split::Weighted => asset.amount,
+                },
+            })
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+    let paid = assets
+        .iter()
+        .map(|asset| Asset {
+            info: asset.info.clone(),
+            amount: Uint128::zero(),
+        })
+        .collect();
+
+    Ok(Round {
+        mode: terms.mode,
+        created_by: created_by.clone(),
+        list_id,
+        group: list.group,
+        split: terms.split,
+        dust: terms.dust,
+        assets,
+        height,
+        block,
+        recipients,
+        total_weight,
+        processed: 0,
+        cursor: None,
+        first_recipient: None,
+        total,
+        paid,
+        finished: false,
+        cancelled: false,
+    })
+}
+
+/// Comma separated attribute for a list of addresses. Attribute values must
+/// not be empty, so an empty list emits nothing.
+fn list_attribute(key: &str, values: &[impl AsRef<str>]) -> Option<Attribute> {
+    if values.is_empty() {
+        return None;
+    }
+    let values: Vec<&str> = values.iter().map(AsRef::as_ref).collect();
+    Some(Attribute::new(key, values.join(",")))
+}
+
+/// Splits `addresses` into those that are not members yet, those that are,
+/// and repeats of an address already seen earlier in the list.
+fn partition_members(
+    addresses: Vec<Addr>,
+    is_member: impl Fn(&Addr) -> StdResult<bool>,
+) -> StdResult<(Vec<Addr>, Vec<Addr>, Vec<Addr>)> {
+    let mut new: Vec<Addr> = vec![];
+    let mut existing: Vec<Addr> = vec![];
+    let mut repeated: Vec<Addr> = vec![];
+    for address in addresses {
+        if new.contains(&address) || existing.contains(&address) {
+            repeated.push(address);
+        } else if is_member(&address)? {
+            existing.push(address);
+        } else {
+            new.push(address);
+        }
+    }
+    Ok((new, existing, repeated))
+}
+
+/// Validates every address before any of them is stored, so one bad entry
+/// leaves the whitelist untouched
+fn validate_addresses(api: &dyn Api, addresses: &[String]) -> StdResult<Vec<Addr>> {
+    addresses.iter().map(|address| api.addr_validate(address)).collect()
+}
+
+/// Id of the list called `name`, or of the default list when no name is given
+fn load_list_id(storage: &dyn Storage, name: Option<&str>) -> Result<u64, ContractError> {
+    let name = name.unwrap_or(DEFAULT_LIST);
+    LIST_IDS
+        .may_load(storage, name)?
+        .ok_or_else(|| ContractError::ListNotFound { name: name.to_string() })
+}
+
+/// `load_list_id` for queries
+fn query_list_id(storage: &dyn Storage, name: Option<&str>) -> StdResult<u64> {
+    load_list_id(storage, name).map_err(|err| StdError::generic_err(err.to_string()))
+}
+
+/// Checks that `name` can be given to a new list
+fn check_list_name(storage: &dyn Storage, name: &str) -> Result<(), ContractError> {
+    if name.is_empty() || name.len() > MAX_LIST_NAME_LENGTH {
+        return Err(ContractError::InvalidListName { name: name.to_string() });
+    }
+    if LIST_IDS.has(storage, name) {
+        return Err(ContractError::ListExists { name: name.to_string() });
+    }
+    Ok(())
+}
+
+fn create_list(storage: &mut dyn Storage, name: &str) -> Result<u64, ContractError> {
+    check_list_name(storage, name)?;
+    let id = LIST_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    LIST_COUNT.save(storage, &id)?;
+    LIST_IDS.save(storage, name, &id)?;
+    LISTS.save(
+        storage,
+        id,
+        &ListInfo {
+            name: name.to_string(),
+            group: None,
+        },
+    )?;
+    Ok(id)
+}
+
+fn new_member(env: &Env, added_by: &Addr, terms: &MemberTerms) -> MemberInfo {
+    MemberInfo {
+        weight: terms.weight.unwrap_or(DEFAULT_WEIGHT),
+        label: terms.label.clone(),
+        added_at_height: env.block.height,
+        added_at_time: env.block.time,
+        added_by: added_by.clone(),
+        expires: terms.expires.unwrap_or_default(),
+    }
+}
+
+/// Stores `member` under `address` in list `list_id` as of `height`, keeping
+/// the list totals, `MEMBER_HISTORY` and the expiry indexes in step. Returns
+/// the entry it replaced.
+fn save_member(
+    storage: &mut dyn Storage,
+    list_id: u64,
+    address: &Addr,
+    member: &MemberInfo,
+    height: u64,
+) -> StdResult<Option<MemberInfo>> {
+    let previous = MEMBERS.may_load(storage, (list_id, address))?;
+    if let Some(previous) = &previous {
+        index_expiry(storage, list_id, previous, false, height)?;
+    }
+    index_expiry(storage, list_id, member, true, height)?;
+    MEMBERS.save(storage, (list_id, address), member, height)?;
+    MEMBER_HISTORY.save(storage, (list_id, address), &Empty {})?;
+    update_list_totals(storage, list_id, previous.as_ref(), Some(member), height)?;
+    Ok(previous)
+}
+
+/// Removes `address` from list `list_id` as of `height`, keeping the list
+/// totals and the expiry indexes in step. Returns the removed entry.
+fn remove_member(
+    storage: &mut dyn Storage,
+    list_id: u64,
+    address: &Addr,
+    height: u64,
+) -> StdResult<Option<MemberInfo>> {
+    let member = match MEMBERS.may_load(storage, (list_id, address))? {
+        Some(member) => member,
+        None => return Ok(None),
+    };
+    index_expiry(storage, list_id, &member, false, height)?;
+    MEMBERS.remove(storage, (list_id, address), height)?;
+    update_list_totals(storage, list_id, Some(&member), None, height)?;
+    Ok(Some(member))
+}
+
+/// Replaces the entry `old` by `new` in the totals of list `list_id` as of
+/// `height`
+fn update_list_totals(
+    storage: &mut dyn Storage,
+    list_id: u64,
+    old: Option<&MemberInfo>,
+    new: Option<&MemberInfo>,
+    height: u64,
+) -> StdResult<()> {
+    let entry = |member: Option<&MemberInfo>| Totals {
+        members: member.is_some() as u64,
+        weight: member.map_or(0, |member| member.weight),
+    };
+    let totals = LIST_TOTALS
+        .may_load(storage, list_id)?
+        .unwrap_or_default()
+        .checked_sub(&entry(old))?
+        .checked_add(&entry(new))?;
+    LIST_TOTALS.save(storage, list_id, &totals, height)
+}
+
+/// Counts `member` of list `list_id` in, or out of, the members expiring at
+/// the point its expiration names, as of `height`
+fn index_expiry(
+    storage: &mut dyn Storage,
+    list_id: u64,
+    member: &MemberInfo,
+    indexed: bool,
+    height: u64,
+) -> StdResult<()> {
+    let (index, at) = match member.expires {
+        Expiration::AtHeight(height) => (EXPIRES_AT_HEIGHT, height),
+        Expiration::AtTime(time) => (EXPIRES_AT_TIME, time.nanos()),
+        Expiration::Never {} => return Ok(()),
+    };
+    let expiring = index.may_load(storage, (list_id, at))?.unwrap_or_default();
+    let entry = Totals { members: 1, weight: member.weight };
+    let expiring = if indexed { expiring.checked_add(&entry)? } else { expiring.checked_sub(&entry)? };
+    // an entry that drops to zero is kept, see `EXPIRES_AT_HEIGHT`
+    index.save(storage, (list_id, at), &expiring, height)
+}
+
+/// Stored members of list `list_id` whose expiration has passed, and their
+/// total weight
+fn expired_totals(storage: &dyn Storage, block: &BlockInfo, list_id: u64) -> StdResult<Totals> {
+    let at_height = Bound::inclusive(block.height);
+    let at_time = Bound::inclusive(block.time.nanos());
+    EXPIRES_AT_HEIGHT
+        .prefix(list_id)
+        .range(storage, None, Some(at_height), Order::Ascending)
+        .chain(EXPIRES_AT_TIME.prefix(list_id).range(storage, None, Some(at_time), Order::Ascending))
+        .try_fold(Totals::default(), |total, item| total.checked_add(&item?.1))
+}
+
+/// The member count and total weight of list `list_id`, leaving out expired
+/// members
+fn active_totals(storage: &dyn Storage, block: &BlockInfo, list_id: u64) -> StdResult<(u64, u64)> {
+    let totals = LIST_TOTALS.may_load(storage, list_id)?.unwrap_or_default();
+    let active = totals.checked_sub(&expired_totals(storage, block, list_id)?)?;
+    Ok((active.members, active.weight))
+}
+
+/// `active_totals` of list `list_id` as it stood at the start of block
+/// `height`, with expirations checked against `block`
+fn active_totals_at_height(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    list_id: u64,
+    height: u64,
+) -> StdResult<(u64, u64)> {
+    let totals = LIST_TOTALS.may_load_at_height(storage, list_id, height)?.unwrap_or_default();
+    let mut expired = Totals::default();
+    for (index, until) in [(EXPIRES_AT_HEIGHT, block.height), (EXPIRES_AT_TIME, block.time.nanos())] {
+        let points = index
+            .prefix(list_id)
+            .keys(storage, None, Some(Bound::inclusive(until)), Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for at in points {
+            if let Some(expiring) = index.may_load_at_height(storage, (list_id, at), height)? {
+                expired = expired.checked_add(&expiring)?;
+            }
+        }
+    }
+    let active = totals.checked_sub(&expired)?;
+    Ok((active.members, active.weight))
+}
+
+fn is_member(storage: &dyn Storage, block: &BlockInfo, list_id: u64, address: &Addr) -> StdResult<bool> {
+    let member = MEMBERS.may_load(storage, (list_id, address))?;
+    Ok(matches!(member, Some(member) if !member.expires.is_expired(block)))
+}
+
+/// Every member of list `list_id` that has not expired, ordered by address
+fn load_members(storage: &dyn Storage, block: &BlockInfo, list_id: u64) -> StdResult<Vec<(Addr, MemberInfo)>> {
+    MEMBERS
+        .prefix(list_id)
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| !matches!(item, Ok((_, member)) if member.expires.is_expired(block)))
+        .collect()
+}
+
+/// The entry of `address` if it was a member of list `list_id` at the start
+/// of block `height`. Expirations by height are checked against `height`;
+/// expirations by time against the current block time, as the time of a past
+/// block is not known.
+fn member_at_height(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    list_id: u64,
+    address: &Addr,
+    height: u64,
+) -> StdResult<Option<MemberInfo>> {
+    let block = BlockInfo { height, ..block.clone() };
+    let member = MEMBERS.may_load_at_height(storage, (list_id, address), height)?;
+    Ok(member.filter(|member| !member.expires.is_expired(&block)))
+}
+
+/// Members of list `list_id` as they stood at the start of block `height`,
+/// ordered by address
+fn members_at_height<'a>(
+    storage: &'a dyn Storage,
+    block: &'a BlockInfo,
+    list_id: u64,
+    height: u64,
+    start_after: Option<&'a Addr>,
+) -> impl Iterator<Item = StdResult<(Addr, MemberInfo)>> + 'a {
+    MEMBER_HISTORY
+        .prefix(list_id)
+        .keys(storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .filter_map(move |address| {
+            let member = address.and_then(|address| {
+                let member = member_at_height(storage, block, list_id, &address, height)?;
+                Ok(member.map(|member| (address, member)))
+            });
+            member.transpose()
+        })
+}
+
+/// The members a distribution pays, with their weights: the current members
+/// of list `list_id`, its members as they stood at a past height, or the
+/// members of the cw4 group it takes its members from
+fn distribution_members(
+    deps: Deps,
+    block: &BlockInfo,
+    list_id: u64,
+    at_height: Option<u64>,
+) -> Result<Vec<(Addr, u64)>, ContractError> {
+    let list = LISTS.load(deps.storage, list_id)?;
+    let members = match (list.group, at_height) {
+        (Some(_), Some(_)) => return Err(ContractError::GroupAtHeight { name: list.name }),
+        (Some(group), None) => {
+            return query_all_members(&deps.querier, group.addr.as_str())?
+                .into_iter()
+                .map(|member| {
+                    let address = deps.api.addr_validate(&member.addr)?;
+                    let weight = if group.use_weights { member.weight } else { DEFAULT_WEIGHT };
+                    Ok((address, weight))
+                })
+                .collect();
+        }
+        (None, None) => load_members(deps.storage, block, list_id)?,
+        (None, Some(height)) if height > block.height => {
+            return Err(ContractError::FutureHeight { height })
+        }
+        (None, Some(height)) => members_at_height(deps.storage, block, list_id, height, None)
+            .collect::<StdResult<_>>()?,
+    };
+    Ok(members
+        .into_iter()
+        .map(|(address, member)| (address, member.weight))
+        .collect())
+}
+
+fn sum_weights(weighted: &[(Addr, u64)]) -> StdResult<u64> {
+    let total = weighted
+        .iter()
+        .try_fold(Uint64::zero(), |total, (_, weight)| total.checked_add(Uint64::new(*weight)))?;
+    Ok(total.u64())
+}
+
+pub fn try_continue_distribution(
+    deps: DepsMut,
+    round_id: u64,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    if ROUNDS.load(deps.storage, round_id)?.is_complete() {
+        return Err(ContractError::RoundComplete { round_id });
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_BATCH_LIMIT).min(MAX_BATCH_LIMIT);
+    let response = pay_round_page(deps, round_id, limit)?;
+    Ok(response.add_attribute("method", "try_continue_distribution"))
+}
+
+/// Abandons an open round. Payouts already made stand; the members not paid
+/// yet are dropped and what is left of the round released from the reserve.
+pub fn try_cancel_round(
+    deps: DepsMut,
+    info: MessageInfo,
+    round_id: u64,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+
+    let mut round = ROUNDS.load(deps.storage, round_id)?;
+    if round.is_complete() {
+        return Err(ContractError::RoundComplete { round_id });
+    }
+
+    for asset in unpaid(&round)? {
+        sub_reserved(deps.storage, &asset.info, asset.amount)?;
+    }
+    round.cancelled = true;
+    ROUNDS.save(deps.storage, round_id, &round)?;
+    OPEN_ROUNDS.remove(deps.storage, (round.list_id, round_id));
+
+    Ok(Response::new()
+        .add_attribute("method", "try_cancel_round")
+        .add_attribute("round_id", round_id.to_string())
+        .add_attribute("dropped", round.recipients.saturating_sub(round.processed).to_string()))
+}
+
+/// Stores `round` under a new id and reserves its total, so the funds cannot
+/// be spent by another distribution while the round is paid out.
+fn create_round(storage: &mut dyn Storage, round: &Round) -> StdResult<u64> {
+    let round_id = ROUND_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    ROUND_COUNT.save(storage, &round_id)?;
+
+    for asset in &round.total {
+        add_reserved(storage, &asset.info, asset.amount)?;
+    }
+    ROUNDS.save(storage, round_id, round)?;
+    OPEN_ROUNDS.save(storage, (round.list_id, round_id), &Empty {})?;
+    Ok(round_id)
+}
+
+/// What is left of each asset of a round, reserved but not paid out yet
+fn unpaid(round: &Round) -> StdResult<Vec<Asset>> {
+    round
+        .total
+        .iter()
+        .zip(&round.paid)
+        .map(|(total, paid)| {
+            Ok(Asset {
+                info: total.info.clone(),
+                amount: total.amount.checked_sub(paid.amount)?,
+            })
+        })
+        .collect()
+}
+
+/// Adds `asset` to the matching entry of `totals`, or appends it.
+fn add_asset(totals: &mut Vec<Asset>, asset: &Asset) -> StdResult<()> {
+    match totals.iter_mut().find(|total| total.info == asset.info) {
+        Some(total) => total.amount = total.amount.checked_add(asset.amount)?,
+        None => totals.push(asset.clone()),
+    }
+    Ok(())
+}
+
+/// The next page of a round
+struct Page {
+    payouts: Vec<Payout>,
+    /// Members of the round on the page, including those whose shares all
+    /// rounded down to zero
+    members: Vec<Addr>,
+    /// Where the following page starts
+    cursor: Option<Addr>,
+    /// Whether no members are left after this page
+    last: bool,
+}
+
+/// Members of a round read for its next page, with their weights
+struct NextMembers {
+    members: Vec<(Addr, u64)>,
+    cursor: Option<Addr>,
+    last: bool,
+}
+
+/// Works out what the members of a round after its cursor receive, looking
+/// at no more than `limit` addresses. Nothing is paid past what is left of
+/// the round, so members that joined a group or list after its snapshot was
+/// taken cannot eat into other rounds' reserves.
+fn plan_page(deps: Deps, round: &Round, limit: u32) -> StdResult<Page> {
+    let NextMembers { members, cursor, last } = match &round.group {
+        Some(group) => next_group_members(deps, group, round.cursor.as_ref(), limit)?,
+        None => next_list_members(deps.storage, round, limit)?,
+    };
+
+    let mut left = unpaid(round)?;
+    let mut payouts = vec![];
+    for (address, weight) in &members {
+        let mut assets = vec![];
+        for (asset, left) in round.assets.iter().zip(left.iter_mut()) {
+            let share = match round.split {
+                Split::Fixed => asset.amount,
+                Split::Even => asset.amount.multiply_ratio(1u64, round.recipients),
+                Split::Weighted => asset.amount.multiply_ratio(*weight, round.total_weight),
+            };
+            let share = share.min(left.amount);
+            left.amount = left.amount.checked_sub(share)?;
+            // nothing to pay of an asset whose share rounded down to zero
+            if !share.is_zero() {
+                assets.push(Asset {
+                    info: asset.info.clone(),
+                    amount: share,
+                });
+            }
+        }
+        if !assets.is_empty() {
+            payouts.push(Payout {
+                recipient: address.to_string(),
+                assets,
+            });
+        }
+    }
+
+    Ok(Page {
+        payouts,
+        members: members.into_iter().map(|(address, _)| address).collect(),
+        cursor,
+        last,
+    })
+}
+
+/// The members of a list round among the next `limit` addresses after its
+/// cursor that were ever on the list
+fn next_list_members(
+    storage: &dyn Storage,
+    round: &Round,
+    limit: u32,
+) -> StdResult<NextMembers> {
+    let mut addresses = MEMBER_HISTORY
+        .prefix(round.list_id)
+        .keys(storage, round.cursor.as_ref().map(Bound::exclusive), None, Order::Ascending)
+        .take(limit as usize + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let last = addresses.len() <= limit as usize;
+    addresses.truncate(limit as usize);
+    let cursor = addresses.last().cloned().or_else(|| round.cursor.clone());
+
+    let mut members = vec![];
+    for address in addresses {
+        let member = MEMBERS.may_load_at_height(storage, (round.list_id, &address), round.height)?;
+        if let Some(member) = member.filter(|member| !member.expires.is_expired(&round.block)) {
+            members.push((address, member.weight));
+        }
+    }
+    Ok(NextMembers { members, cursor, last })
+}
+
+/// The next `limit` members of the cw4 group `group` after `start_after`
+fn next_group_members(
+    deps: Deps,
+    group: &GroupSource,
+    start_after: Option<&Addr>,
+    limit: u32,
+) -> StdResult<NextMembers> {
+    let (members, last) = query_members_after(
+        &deps.querier,
+        group.addr.as_str(),
+        start_after.map(Addr::to_string),
+        limit as usize,
+    )?;
+    let members = members
+        .into_iter()
+        .map(|member| {
+            let address = deps.api.addr_validate(&member.addr)?;
+            let weight = if group.use_weights { member.weight } else { DEFAULT_WEIGHT };
+            Ok((address, weight))
+        })
+        .collect::<StdResult<Vec<(Addr, u64)>>>()?;
+    let cursor = members.last().map(|(address, _)| address.clone()).or_else(|| start_after.cloned());
+    Ok(NextMembers { members, cursor, last })
+}
+
+/// Pays the next page of a round, looking at no more than `limit` members.
+/// The last page also settles the round's dust by its `DustRule`.
+fn pay_round_page(deps: DepsMut, round_id: u64, limit: u32) -> Result<Response, ContractError> {
+    let mut round = ROUNDS.load(deps.storage, round_id)?;
+    let page = plan_page(deps.as_ref(), &round, limit)?;
+    let mut payouts = page.payouts;
+    round.processed += page.members.len() as u64;
+    round.cursor = page.cursor;
+    if round.first_recipient.is_none() {
+        round.first_recipient = page.members.into_iter().next();
+    }
+    for payout in &payouts {
+        for asset in &payout.assets {
+            add_asset(&mut round.paid, asset)?;
+        }
+    }
+
+    let mut refund = vec![];
+    let mut totals = vec![];
+    if page.last {
+        round.finished = true;
+        OPEN_ROUNDS.remove(deps.storage, (round.list_id, round_id));
+        let dust = unpaid(&round)?;
+        let leftover: Vec<Asset> = dust.iter().filter(|d| !d.amount.is_zero()).cloned().collect();
+        let recipient = match round.dust {
+            DustRule::Retain => None,
+            DustRule::Owner => STATE.load(deps.storage)?.owner,
+            DustRule::FirstMember => round.first_recipient.clone(),
+            DustRule::Sender => Some(round.created_by.clone()),
+        };
+        match recipient {
+            // the sender's refund is not one of the round's payouts
+            Some(sender) if round.dust == DustRule::Sender => {
+                refund = transfer_msgs(sender.as_str(), &leftover)?;
+                for asset in &leftover {
+                    sub_reserved(deps.storage, &asset.info, asset.amount)?;
+                }
+            }
+            Some(recipient) => {
+                match payouts.iter_mut().find(|payout| payout.recipient == recipient.as_str()) {
+                    Some(payout) => {
+                        for asset in &leftover {
+                            add_asset(&mut payout.assets, asset)?;
+                        }
+                    }
+                    None if !leftover.is_empty() => payouts.push(Payout {
+                        recipient: recipient.into(),
+                        assets: leftover.clone(),
+                    }),
+                    None => {}
+                }
+                for asset in &leftover {
+                    add_asset(&mut round.paid, asset)?;
+                }
+            }
+            // retained dust stays in the contract, free for later distributions
+            None => {
+                for asset in &leftover {
+                    sub_reserved(deps.storage, &asset.info, asset.amount)?;
+                }
+            }
+        }
+        totals = round
+            .paid
+            .iter()
+            .zip(&dust)
+            .map(|(paid, dust)| {
+                Event::new("distribute_total")
+                    .add_attribute("asset", paid.info.to_string())
+                    .add_attribute("total_amount", paid.amount)
+                    .add_attribute("dust", dust.amount)
+            })
+            .collect::<Vec<_>>();
+    }
+    ROUNDS.save(deps.storage, round_id, &round)?;
+
+    let response = match round.mode {
+        PayoutMode::Push => {
+            // sent funds leave the contract, so they are no longer reserved
+            for payout in &payouts {
+                for asset in &payout.assets {
+                    sub_reserved(deps.storage, &asset.info, asset.amount)?;
+                }
+            }
+            push_payouts(payouts)?
+        }
+        // credited funds stay reserved until claimed
+        PayoutMode::Accrue => accrue_payouts(deps, payouts)?,
+    };
+
+    let remaining = if round.finished { 0 } else { round.recipients.saturating_sub(round.processed) };
+    Ok(response
+        .add_messages(refund)
+        .add_events(totals)
+        .add_attribute("round_id", round_id.to_string())
+        .add_attribute("processed", round.processed.to_string())
+        .add_attribute("remaining", remaining.to_string()))
+}
+
+/// Sends every recipient their share directly from the contract balance.
+/// All native coins for one recipient go out in a single bank send.
+fn push_payouts(payouts: Vec<Payout>) -> StdResult<Response> {
+    let mut messages = vec![];
+    let mut events: Vec<Event> = vec![];
+    for payout in payouts {
+        for asset in &payout.assets {
+            events.push(
+                Event::new("distribute_transfer")
+                    .add_attribute("recipient", payout.recipient.clone())
+                    .add_attribute("asset", asset.info.to_string())
+                    .add_attribute("amount", asset.amount),
+            );
+        }
+        messages.extend(transfer_msgs(&payout.recipient, &payout.assets)?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_events(events))
+}
+
+/// Credits every recipient in the claim ledger. The funds stay in the
+/// contract, reserved, until each member withdraws them with `Claim`.
+fn accrue_payouts(deps: DepsMut, payouts: Vec<Payout>) -> Result<Response, ContractError> {
+    let mut events: Vec<Event> = vec![];
+    for payout in payouts {
+        let recipient = deps.api.addr_validate(&payout.recipient)?;
+        for asset in payout.assets {
+            CLAIMABLE.update(
+                deps.storage,
+                (&recipient, &asset.info.key()),
+                |owed| -> StdResult<_> { Ok(owed.unwrap_or_default().checked_add(asset.amount)?) },
+            )?;
+            events.push(
+                Event::new("distribute_accrual")
+                    .add_attribute("recipient", recipient.clone())
+                    .add_attribute("asset", asset.info.to_string())
+                    .add_attribute("amount", asset.amount),
+            );
+        }
+    }
+
+    Ok(Response::new().add_events(events))
+}
+
+pub fn try_claim(deps: DepsMut, info: MessageInfo, asset: AssetInfo) -> Result<Response, ContractError> {
+    let asset = asset.validate(deps.api)?;
+    let key = asset.key();
+    let owed = CLAIMABLE
+        .may_load(deps.storage, (&info.sender, &key))?
+        .unwrap_or_default();
+    if owed.is_zero() {
+        return Err(ContractError::NothingToClaim { asset: asset.to_string() });
+    }
+
+    CLAIMABLE.remove(deps.storage, (&info.sender, &key));
+    sub_reserved(deps.storage, &asset, owed)?;
+
+    Ok(Response::new()
+        .add_message(asset.transfer_msg(info.sender.as_str(), owed)?)
+        .add_attribute("method", "try_claim")
+        .add_attribute("recipient", info.sender)
+        .add_attribute("asset", asset.to_string())
+        .add_attribute("amount", owed))
+}
+
+/// Handles CW20 tokens sent to the contract with `Cw20ExecuteMsg::Send`.
+/// `info.sender` is the token contract, `msg.sender` the original sender.
+pub fn try_receive(info: MessageInfo, msg: Cw20ReceiveMsg) -> Result<Response, ContractError> {
+    match from_binary(&msg.msg)? {
+        ReceiveMsg::Fund {} => Ok(Response::new()
+            .add_attribute("method", "try_receive")
+            .add_attribute("action", "fund")
+            .add_attribute("sender", msg.sender)
+            .add_attribute("asset", info.sender)
+            .add_attribute("amount", msg.amount)),
+    }
+}
+
+fn add_reserved(storage: &mut dyn Storage, asset: &AssetInfo, amount: Uint128) -> StdResult<Uint128> {
+    RESERVED.update(storage, &asset.key(), |reserved| -> StdResult<_> {
+        Ok(reserved.unwrap_or_default().checked_add(amount)?)
+    })
+}
+
+fn sub_reserved(storage: &mut dyn Storage, asset: &AssetInfo, amount: Uint128) -> StdResult<Uint128> {
+    RESERVED.update(storage, &asset.key(), |reserved| -> StdResult<_> {
+        Ok(reserved.unwrap_or_default().checked_sub(amount)?)
+    })
+}
+
+/// Returns the part of the contract balance of `asset` that is not reserved
+/// for pending claims or open rounds.
+fn query_free_balance(deps: Deps, env: &Env, asset: &AssetInfo) -> StdResult<Uint128> {
+    let contract_balance = asset.query_balance(&deps.querier, &env.contract.address)?;
+    let reserved = RESERVED.may_load(deps.storage, &asset.key())?.unwrap_or_default();
+    Ok(contract_balance.saturating_sub(reserved))
+}
+
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // Older versions kept the whitelist as a `Vec<String>` inside `STATE` and
+    // the weights in a separate map. Move both into the default list.
+    let old = legacy::STATE.load(deps.storage)?;
+    let added_by = old.owner.clone().unwrap_or_else(|| env.contract.address.clone());
+    let list_id = match LIST_IDS.may_load(deps.storage, DEFAULT_LIST)? {
+        Some(list_id) => list_id,
+        None => create_list(deps.storage, DEFAULT_LIST)?,
+    };
+    let mut migrated = 0u64;
+    let mut skipped = vec![];
+    for address in old.whitelist {
+        let weight = legacy::WEIGHTS
+            .may_load(deps.storage, &address)?
+            .unwrap_or(DEFAULT_WEIGHT);
+        legacy::WEIGHTS.remove(deps.storage, &address);
+        match deps.api.addr_validate(&address) {
+            Ok(address) => {
+                let terms = MemberTerms { weight: Some(weight), ..MemberTerms::default() };
+                let member = new_member(&env, &added_by, &terms);
+                save_member(deps.storage, list_id, &address, &member, env.block.height)?;
+                migrated += 1;
+            }
+            // such an entry could never be paid; drop it rather than fail the migration
+            Err(_) => skipped.push(address),
+        }
+    }
+    STATE.save(
+        deps.storage,
+        &State {
+            count: old.count,
+            owner: old.owner,
+        },
+    )?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "migrate")
+        .add_attribute("migrated", migrated.to_string())
+        .add_attributes(list_attribute("skipped", &skipped)))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetCount {} => to_binary(&query_count(deps)?),
+        QueryMsg::Owner {} => to_binary(&query_owner(deps)?),
+        QueryMsg::ListRoles {} => to_binary(&query_list_roles(deps)?),
+        QueryMsg::Timelock {} => to_binary(&TimelockResponse {
+            timelock: TIMELOCK.may_load(deps.storage)?,
+        }),
+        QueryMsg::QueuedActions { start_after, limit } => {
+            to_binary(&query_queued_actions(deps, start_after, limit)?)
+        }
+        QueryMsg::Approvers {} => to_binary(&ApproversResponse {
+            config: APPROVAL_CONFIG.may_load(deps.storage)?,
+        }),
+        QueryMsg::PendingDistributions { start_after, limit } => {
+            to_binary(&query_pending_distributions(deps, start_after, limit)?)
+        }
+        QueryMsg::PauseStatus {} => to_binary(&query_pause_status(deps)?),
+        QueryMsg::GetWhitelist { list } => to_binary(&query_whitelist(deps, env, list)?),
+        QueryMsg::ListMembers { start_after, limit, list } => {
+            to_binary(&query_list_members(deps, env, start_after, limit, list)?)
+        }
+        QueryMsg::PendingApplications { start_after, limit, list } => {
+            to_binary(&query_pending_applications(deps, start_after, limit, list)?)
+        }
+        QueryMsg::IsMember { address, list } => {
+            to_binary(&query_is_member(deps, env, address, list)?)
+        }
+        QueryMsg::MemberCount { list } => to_binary(&query_member_count(deps, env, list)?),
+        QueryMsg::MemberDetails { address, list } => {
+            to_binary(&query_member_details(deps, env, address, list)?)
+        }
+        QueryMsg::IsMemberAtHeight { address, height, list } => {
+            to_binary(&query_is_member_at_height(deps, env, address, height, list)?)
+        }
+        QueryMsg::ListMembersAtHeight { height, start_after, limit, list } => to_binary(
+            &query_list_members_at_height(deps, env, height, start_after, limit, list)?,
+        ),
+        QueryMsg::Lists { start_after, limit } => {
+            to_binary(&query_lists(deps, env, start_after, limit)?)
+        }
+        QueryMsg::Member { addr, at_height, list } => {
+            to_binary(&query_member(deps, env, addr, at_height, list)?)
+        }
+        QueryMsg::TotalWeight { list } => to_binary(&query_total_weight(deps, env, list)?),
+        QueryMsg::Admin {} => to_binary(&query_admin(deps)?),
+        QueryMsg::Hooks { list } => to_binary(&query_hooks(deps, list)?),
+        QueryMsg::Claimable { address } => to_binary(&query_claimable(deps, address)?),
+        QueryMsg::Shares { list } => to_binary(&query_shares(deps, env, list)?),
+        QueryMsg::Round { id } => to_binary(&query_round(deps, id)?),
+        QueryMsg::SimulateDistribution { amount, asset, split, list } => {
+            let split = split.unwrap_or_default();
+            to_binary(&query_simulate_distribution(deps, env, amount, asset, split, list)?)
+        }
+    }
+}
+
+fn query_count(deps: Deps) -> StdResult<CountResponse> {
+    let state = STATE.load(deps.storage)?;
+    Ok(CountResponse { count: state.count })
+}
+
+fn query_owner(deps: Deps) -> StdResult<OwnerResponse> {
+    let state = STATE.load(deps.storage)?;
+    let pending = PENDING_OWNER.may_load(deps.storage)?;
+    Ok(OwnerResponse {
+        owner: state.owner.map(String::from),
+        pending_owner: pending.as_ref().map(|pending| pending.address.to_string()),
+        pending_expiry: pending.map(|pending| pending.expiry),
+    })
+}
+
+fn query_queued_actions(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<QueuedActionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let actions = QUEUE
+        .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(id, action)| QueuedActionInfo { id, action }))
+        .collect::<StdResult<_>>()?;
+    Ok(QueuedActionsResponse { actions })
+}
+
+fn query_pending_distributions(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PendingDistributionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let distributions = PENDING_DISTRIBUTIONS
+        .range(deps.storage, start_after.map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(id, distribution)| PendingDistributionInfo { id, distribution }))
+        .collect::<StdResult<_>>()?;
+    Ok(PendingDistributionsResponse { distributions })
+}
+
+fn query_pause_status(deps: Deps) -> StdResult<PauseStatusResponse> {
+    let last_change = PAUSE.may_load(deps.storage)?;
+    Ok(PauseStatusResponse {
+        paused: matches!(&last_change, Some(pause) if pause.paused),
+        last_change,
+    })
+}
+
+fn query_list_roles(deps: Deps) -> StdResult<ListRolesResponse> {
+    let roles = Role::ALL
+        .iter()
+        .map(|role| {
+            let addresses = ROLES
+                .prefix(role.key())
+                .keys(deps.storage, None, None, Order::Ascending)
+                .map(|address| address.map(String::from))
+                .collect::<StdResult<_>>()?;
+            Ok(RoleHolders { role: *role, addresses })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(ListRolesResponse { roles })
+}
+
+fn query_whitelist(deps: Deps, env: Env, list: Option<String>) -> StdResult<WhitelistResponse> {
+    let list_id = query_list_id(deps.storage, list.as_deref())?;
+    let whitelist = load_members(deps.storage, &env.block, list_id)?
+        .into_iter()
+        .map(|(address, _)| address.into())
+        .collect();
+    Ok(WhitelistResponse { whitelist })
+}
+
+fn query_list_members(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    list: Option<String>,
+) -> StdResult<MemberListResponse> {
+    let list_id = query_list_id(deps.storage, list.as_deref())?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let members = MEMBERS
+        .prefix(list_id)
+        .range(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .filter(|item| !matches!(item, Ok((_, member)) if member.expires.is_expired(&env.block)))
+        .take(limit)
+        .map(|item| {
+            let (address, member) = item?;
+            Ok(Member {
+                addr: address.into(),
+                weight: member.weight,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(MemberListResponse { members })
+}
+
+fn query_pending_applications(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    list: Option<String>,
+) -> StdResult<PendingApplicationsResponse> {
+    let list_id = query_list_id(deps.storage, list.as_deref())?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let applications = APPLICATIONS
+        .prefix(list_id)
+        .range(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (address, application) = item?;
+            Ok(PendingApplication { address: address.into(), application })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(PendingApplicationsResponse { applications })
+}
+
+fn query_is_member(
+    deps: Deps,
+    env: Env,
+    address: String,
+    list: Option<String>,
+) -> StdResult<IsMemberResponse> {
+    let list_id = query_list_id(deps.storage, list.as_deref())?;
+    let address = deps.api.addr_validate(&address)?;
+    Ok(IsMemberResponse {
+        is_member: is_member(deps.storage, &env.block, list_id, &address)?,
+    })
+}
+
+fn query_member_count(deps: Deps, env: Env, list: Option<String>) -> StdResult<MemberCountResponse> {
+    let list_id = query_list_id(deps.storage, list.as_deref())?;
+    let (count, _) = active_totals(deps.storage, &env.block, list_id)?;
+    Ok(MemberCountResponse { count })
+}
+
+fn query_member_details(
+    deps: Deps,
+    env: Env,
+    address: String,
+    list: Option<String>,
+) -> StdResult<MemberDetailsResponse> {
+    let list_id = query_list_id(deps.storage, list.as_deref())?;
+    let address = deps.api.addr_validate(&address)?;
+    let member = MEMBERS.may_load(deps.storage, (list_id, &address))?;
+    let expired = matches!(&member, Some(member) if member.expires.is_expired(&env.block));
+    Ok(MemberDetailsResponse {
+        address: address.into(),
+        member,
+        expired,
+    })
+}
+
+fn query_is_member_at_height(
+    deps: Deps,
+    env: Env,
+    address: String,
+    height: u64,
+    list: Option<String>,
+) -> StdResult<IsMemberResponse> {
+    let list_id = query_list_id(deps.storage, list.as_deref())?;
+    let address = deps.api.addr_validate(&address)?;
+    let member = member_at_height(deps.storage, &env.block, list_id, &address, height)?;
+    Ok(IsMemberResponse {
+        is_member: member.is_some(),
+    })
+}
+
+fn query_list_members_at_height(
+    deps: Deps,
+    env: Env,
+    height: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    list: Option<String>,
+) -> StdResult<MemberListResponse> {
+    let list_id = query_list_id(deps.storage, list.as_deref())?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let members = members_at_height(deps.storage, &env.block, list_id, height, start_after.as_ref())
+        .take(limit)
+        .map(|item| {
+            let (address, member) = item?;
+            Ok(Member {
+                addr: address.into(),
+                weight: member.weight,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(MemberListResponse { members })
+}
+
+fn query_lists(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ListsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let lists = LIST_IDS
+        .range(
+            deps.storage,
+            start_after.as_deref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (name, list_id) = item?;
+            let (member_count, _) = active_totals(deps.storage, &env.block, list_id)?;
+            let group = LISTS.load(deps.storage, list_id)?.group;
+            Ok(ListSummary {
+                name,
+                member_count,
+                group: group.map(|group| group.addr.into()),
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(ListsResponse { lists })
+}
+
+/// cw4 `Member`: the weight of `addr`, now or at the start of block `at_height`
+fn query_member(
+    deps: Deps,
+    env: Env,
+    addr: String,
+    at_height: Option<u64>,
+    list: Option<String>,
+) -> StdResult<MemberResponse> {
+    let list_id = query_list_id(deps.storage, list.as_deref())?;
+    let address = deps.api.addr_validate(&addr)?;
+    let member = match at_height {
+        Some(height) => member_at_height(deps.storage, &env.block, list_id, &address, height)?,
+        None => MEMBERS
+            .may_load(deps.storage, (list_id, &address))?
+            .filter(|member| !member.expires.is_expired(&env.block)),
+    };
+    Ok(MemberResponse {
+        weight: member.map(|member| member.weight),
+    })
+}
+
+fn query_total_weight(deps: Deps, env: Env, list: Option<String>) -> StdResult<TotalWeightResponse> {
+    let list_id = query_list_id(deps.storage, list.as_deref())?;
+    let (_, weight) = active_totals(deps.storage, &env.block, list_id)?;
+    Ok(TotalWeightResponse { weight })
+}
+
+fn query_admin(deps: Deps) -> StdResult<AdminResponse> {
+    let state = STATE.load(deps.storage)?;
+    Ok(AdminResponse {
+        admin: state.owner.map(String::from),
+    })
+}
+
+fn query_hooks(deps: Deps, list: Option<String>) -> StdResult<HooksResponse> {
+    let list_id = query_list_id(deps.storage, list.as_deref())?;
+    let hooks = HOOKS
+        .prefix(list_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|hook| hook.map(String::from))
+        .collect::<StdResult<_>>()?;
+    Ok(HooksResponse { hooks })
+}
+
+fn query_shares(deps: Deps, env: Env, list: Option<String>) -> StdResult<SharesResponse> {
+    let list_id = query_list_id(deps.storage, list.as_deref())?;
+    let weighted = distribution_members(deps, &env.block, list_id, None)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    let total_weight = sum_weights(&weighted)?;
+
+    let shares = weighted
+        .into_iter()
+        .map(|(address, weight)| ShareInfo {
+            address: address.into(),
+            weight,
+            share: if total_weight == 0 {
+                Decimal::zero()
+            } else {
+                Decimal::from_ratio(weight, total_weight)
+            },
+        })
+        .collect();
+    Ok(SharesResponse { total_weight, shares })
+}
+
+/// Runs the planning step of `Distribute` without storing or sending anything.
+fn query_simulate_distribution(
+    deps: Deps,
+    env: Env,
+    amount: Uint128,
+    asset: AssetInfo,
+    split: Split,
+    list: Option<String>,
+) -> StdResult<SimulationResponse> {
+    let asset = asset.validate(deps.api)?;
+    let assets = vec![Asset { info: asset.clone(), amount }];
+    let terms = DistributionTerms { split, list, ..DistributionTerms::default() };
+    let mut round = new_round(deps, &env, &env.contract.address, assets, &terms)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    let mut payouts = vec![];
+    loop {
+        let page = plan_page(deps, &round, MAX_BATCH_LIMIT)?;
+        round.cursor = page.cursor;
+        for payout in &page.payouts {
+            for asset in &payout.assets {
+                add_asset(&mut round.paid, asset)?;
+            }
+        }
+        payouts.extend(page.payouts);
+        if page.last {
+            break;
+        }
+    }
+
+    let required = round.total[0].amount;
+    let balance = asset.query_balance(&deps.querier, &env.contract.address)?;
+    let available = query_free_balance(deps, &env, &asset)?;
+    Ok(SimulationResponse {
+        asset,
+        payouts,
+        required,
+        dust: unpaid(&round)?[0].amount,
+        balance,
+        available,
+        insufficient: available < required,
+    })
+}
+
+fn query_round(deps: Deps, id: u64) -> StdResult<RoundResponse> {
+    let round = ROUNDS.load(deps.storage, id)?;
+    let pending = if round.is_complete() {
+        vec![]
+    } else {
+        plan_page(deps, &round, DEFAULT_BATCH_LIMIT)?.payouts
+    };
+
+    Ok(RoundResponse {
+        id,
+        complete: round.is_complete(),
+        mode: round.mode,
+        created_by: round.created_by.into(),
+        recipients: round.recipients,
+        processed: round.processed,
+        cancelled: round.cancelled,
+        total: round.total,
+        paid: round.paid,
+        pending,
+    })
+}
+
+fn query_claimable(deps: Deps, address: String) -> StdResult<ClaimableResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let claimable = CLAIMABLE
+        .prefix(&address)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (key, amount) = item?;
+            Ok(Asset { info: AssetInfo::from_key(&key)?, amount })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(ClaimableResponse { address: address.into(), claimable })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{
+        mock_dependencies_with_balance, mock_env, mock_info, MOCK_CONTRACT_ADDR,
+    };
+    use cosmwasm_std::{attr, coin, coins, BankMsg, SubMsg};
+
+    #[test]
+    fn proper_initialization_ops() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = InstantiateMsg { count: 17 , whitelist: vec![] };
+        let info = mock_info("creator", &coins(1000, "earth"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // it worked, let's query the state
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCount {}).unwrap();
+        let value: CountResponse = from_binary(&res).unwrap();
+        assert_eq!(17, value.count);
+    }
+
+    #[test]
+    fn increment() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec![]  };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // beneficiary can release it
+        let info = mock_info("anyone", &coins(2, "token"));
+        let msg = ExecuteMsg::Increment {};
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // should increase counter by 1
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCount {}).unwrap();
+        let value: CountResponse = from_binary(&res).unwrap();
+        assert_eq!(18, value.count);
+    }
+
+    #[test]
+    fn reset() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec![]  };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // beneficiary can release it
+        let unauth_info = mock_info("anyone", &coins(2, "token"));
+        let msg = ExecuteMsg::Reset { count: 5 };
+        let res = execute(deps.as_mut(), mock_env(), unauth_info, msg);
+        match res {
+            Err(ContractError::MissingRole { role }) => assert_eq!("counter_admin", role),
+            _ => panic!("Must return missing role error"),
+        }
+
+        // only the original creator can reset the counter
+        let auth_info = mock_info("creator", &coins(2, "token"));
+        let msg = ExecuteMsg::Reset { count: 5 };
+        let _res = execute(deps.as_mut(), mock_env(), auth_info, msg).unwrap();
+
+        // should now be 5
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCount {}).unwrap();
+        let value: CountResponse = from_binary(&res).unwrap();
+        assert_eq!(5, value.count);
+    }
+
+    #[test]
+    fn ownership_is_transferred_in_two_steps() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec![] };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::ProposeOwner { new_owner: "successor".to_string(), expiry: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("successor", &[]), msg.clone());
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let value: OwnerResponse = from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Owner {}).unwrap()).unwrap();
+        assert_eq!(Some("creator".to_string()), value.owner);
+        assert_eq!(Some("successor".to_string()), value.pending_owner);
+
+        // nothing changes until the transfer is accepted
+        let msg = ExecuteMsg::AcceptOwnership {};
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg.clone());
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        execute(deps.as_mut(), mock_env(), mock_info("successor", &[]), msg.clone()).unwrap();
+        let value: OwnerResponse = from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Owner {}).unwrap()).unwrap();
+        assert_eq!(Some("successor".to_string()), value.owner);
+        assert_eq!(None, value.pending_owner);
+
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Reset { count: 5 });
+        match res {
+            Err(ContractError::MissingRole { role }) => assert_eq!("counter_admin", role),
+            _ => panic!("Must return missing role error"),
+        }
+        let successor = mock_info("successor", &[]);
+        execute(deps.as_mut(), mock_env(), successor.clone(), ExecuteMsg::Reset { count: 5 }).unwrap();
+
+        // an expired proposal cannot be accepted
+        let mut env = mock_env();
+        let msg = ExecuteMsg::ProposeOwner {
+            new_owner: "creator".to_string(),
+            expiry: Some(Expiration::AtHeight(env.block.height + 10)),
+        };
+        execute(deps.as_mut(), env.clone(), successor.clone(), msg).unwrap();
+        env.block.height += 10;
+        let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::AcceptOwnership {});
+        match res {
+            Err(ContractError::OwnershipProposalExpired {}) => {}
+            _ => panic!("Must return proposal expired error"),
+        }
+        execute(deps.as_mut(), env.clone(), successor.clone(), ExecuteMsg::CancelOwnershipProposal {}).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), successor.clone(), ExecuteMsg::CancelOwnershipProposal {});
+        match res {
+            Err(ContractError::NoPendingOwner {}) => {}
+            _ => panic!("Must return no pending owner error"),
+        }
+
+        execute(deps.as_mut(), env.clone(), successor.clone(), ExecuteMsg::RenounceOwnership {}).unwrap();
+        let value: OwnerResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Owner {}).unwrap()).unwrap();
+        assert_eq!(None, value.owner);
+        let res = execute(deps.as_mut(), env, successor, ExecuteMsg::Reset { count: 1 });
+        match res {
+            Err(ContractError::MissingRole { role }) => assert_eq!("counter_admin", role),
+            _ => panic!("Must return missing role error"),
+        }
+    }
+
+    
+    #[test]
+    fn roles_grant_their_actions_only() {
+        let mut deps = mock_dependencies_with_balance(&coins(100, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec!["address1".to_string()] };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::GrantRole { role: Role::WhitelistManager, address: "manager".to_string() };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("manager", &[]), msg.clone());
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::RoleAlreadyGranted { .. }) => {}
+            _ => panic!("Must return role already granted error"),
+        }
+        let msg = ExecuteMsg::GrantRole { role: Role::Distributor, address: "treasurer".to_string() };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let value: ListRolesResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::ListRoles {}).unwrap()).unwrap();
+        assert_eq!(
+            vec![
+                RoleHolders { role: Role::WhitelistManager, addresses: vec!["manager".to_string()] },
+                RoleHolders { role: Role::Distributor, addresses: vec!["treasurer".to_string()] },
+                RoleHolders { role: Role::CounterAdmin, addresses: vec![] },
+                RoleHolders { role: Role::Pauser, addresses: vec![] },
+            ],
+            value.roles
+        );
+
+        let manager = mock_info("manager", &[]);
+        let msg = ExecuteMsg::Add {
+            whitelist: vec!["address2".to_string()],
+            weight: None,
+            strict: None,
+            label: None,
+            expires: None,
+            list: None,
+        };
+        execute(deps.as_mut(), mock_env(), manager.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::new(10),
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            at_height: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), manager.clone(), msg.clone());
+        match res {
+            Err(ContractError::MissingRole { role }) => assert_eq!("distributor", role),
+            _ => panic!("Must return missing role error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), mock_info("treasurer", &[]), msg).unwrap();
+        assert_eq!(2, res.messages.len());
+        // roles do not extend to owner-only actions
+        let msg = ExecuteMsg::CreateList { name: "team".to_string() };
+        let res = execute(deps.as_mut(), mock_env(), manager.clone(), msg);
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        let msg = ExecuteMsg::RevokeRole { role: Role::WhitelistManager, address: "manager".to_string() };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::RoleNotGranted { .. }) => {}
+            _ => panic!("Must return role not granted error"),
+        }
+        let msg = ExecuteMsg::Remove { whitelist: vec!["address2".to_string()], strict: None, list: None };
+        let res = execute(deps.as_mut(), mock_env(), manager, msg);
+        match res {
+            Err(ContractError::MissingRole { role }) => assert_eq!("whitelist_manager", role),
+            _ => panic!("Must return missing role error"),
+        }
+    }
+
+    #[test]
+    fn pause_stops_state_changes() {
+        let mut deps = mock_dependencies_with_balance(&coins(100, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec!["address1".to_string()] };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("guard", &[]), ExecuteMsg::Pause {});
+        match res {
+            Err(ContractError::MissingRole { role }) => assert_eq!("pauser", role),
+            _ => panic!("Must return missing role error"),
+        }
+        let msg = ExecuteMsg::GrantRole { role: Role::Pauser, address: "guard".to_string() };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let guard = mock_info("guard", &[]);
+        execute(deps.as_mut(), mock_env(), guard.clone(), ExecuteMsg::Pause {}).unwrap();
+
+        let value: PauseStatusResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus {}).unwrap()).unwrap();
+        assert!(value.paused);
+        assert_eq!(Addr::unchecked("guard"), value.last_change.unwrap().set_by);
+
+        let paused = [
+            ExecuteMsg::Increment {},
+            ExecuteMsg::Add {
+                whitelist: vec!["address2".to_string()],
+                weight: None,
+                strict: None,
+                label: None,
+                expires: None,
+                list: None,
+            },
+            ExecuteMsg::Remove { whitelist: vec!["address1".to_string()], strict: None, list: None },
+            ExecuteMsg::Distribute {
+                amount: Uint128::new(10),
+                asset: AssetInfo::native("token"),
+                mode: None,
+                split: None,
+                at_height: None,
+                list: None,
+            },
+        ];
+        for msg in paused {
+            // even the owner is stopped
+            let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+            match res {
+                Err(ContractError::Paused {}) => {}
+                _ => panic!("Must return paused error"),
+            }
+        }
+
+        // ownership can still be moved while paused
+        let msg = ExecuteMsg::ProposeOwner { new_owner: "successor".to_string(), expiry: None };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::CancelOwnershipProposal {}).unwrap();
+
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Unpause {}).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), guard, ExecuteMsg::Unpause {});
+        match res {
+            Err(ContractError::NotPaused {}) => {}
+            _ => panic!("Must return not paused error"),
+        }
+        let value: PauseStatusResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PauseStatus {}).unwrap()).unwrap();
+        assert!(!value.paused);
+        assert_eq!(Addr::unchecked("creator"), value.last_change.unwrap().set_by);
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Increment {}).unwrap();
+    }
+
+    #[test]
+    fn pause_stops_every_payout_but_not_claims() {
+        let mut deps = mock_dependencies_with_balance(&coins(100, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec!["address1".to_string()] };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::new(40),
+            asset: AssetInfo::native("token"),
+            mode: Some(PayoutMode::Accrue),
+            split: None,
+            at_height: None,
+            list: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::Pause {}).unwrap();
+
+        let paused = [
+            ExecuteMsg::SetWhitelist {
+                members: vec!["address2".to_string()],
+                weight: None,
+                strict: None,
+                list: None,
+            },
+            ExecuteMsg::Approve { addresses: vec!["address2".to_string()], list: None },
+            ExecuteMsg::DistributeMany {
+                assets: vec![Asset { info: AssetInfo::native("token"), amount: Uint128::new(10) }],
+                mode: None,
+                split: None,
+                list: None,
+            },
+            ExecuteMsg::DistributeBalance {
+                asset: AssetInfo::native("token"),
+                mode: None,
+                split: None,
+                dust: None,
+                list: None,
+            },
+            ExecuteMsg::DistributeFunds { mode: None, split: None, list: None },
+            ExecuteMsg::ContinueDistribution { round_id: 1, limit: None },
+            ExecuteMsg::ApproveDistribution { id: 1 },
+            ExecuteMsg::ExecuteQueued { id: 1 },
+        ];
+        for msg in paused {
+            let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &coins(10, "token")), msg);
+            match res {
+                Err(ContractError::Paused {}) => {}
+                _ => panic!("Must return paused error"),
+            }
+        }
+
+        // credited funds can still be withdrawn
+        let msg = ExecuteMsg::Claim { asset: AssetInfo::native("token") };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("address1", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "address1".to_string(),
+                amount: coins(40, "token"),
+            })]
+        );
+    }
+
+    #[test]
+    fn timelocked_actions_wait_in_the_queue() {
+        let mut deps = mock_dependencies_with_balance(&coins(1000, "token"));
+
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec!["address1".to_string(), "address2".to_string()],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let reset = ExecuteMsg::Reset { count: 5 };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::QueueAction { msg: Box::new(reset.clone()) });
+        match res {
+            Err(ContractError::NoTimelock {}) => {}
+            _ => panic!("Must return no timelock error"),
+        }
+        let timelock = TimelockConfig {
+            delay: 3600,
+            distribute_threshold: Uint128::new(100),
+            bulk_remove_threshold: 1,
+        };
+        let msg = ExecuteMsg::SetTimelock { timelock: Some(timelock) };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), reset.clone());
+        match res {
+            Err(ContractError::TimelockRequired {}) => {}
+            _ => panic!("Must return timelock required error"),
+        }
+        let msg = ExecuteMsg::QueueAction { msg: Box::new(reset) };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert!(res.attributes.contains(&attr("action_id", "1")));
+
+        let msg = QueryMsg::QueuedActions { start_after: None, limit: None };
+        let value: QueuedActionsResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(1, value.actions.len());
+        assert_eq!(mock_env().block.time.plus_seconds(3600), value.actions[0].action.ready_at);
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), ExecuteMsg::ExecuteQueued { id: 1 });
+        match res {
+            Err(ContractError::TimelockNotExpired { id: 1, .. }) => {}
+            _ => panic!("Must return timelock not expired error"),
+        }
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(3600);
+        execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), ExecuteMsg::ExecuteQueued { id: 1 }).unwrap();
+        let value: CountResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::GetCount {}).unwrap()).unwrap();
+        assert_eq!(5, value.count);
+
+        // 2 * 50 is within the threshold, 2 * 60 is not
+        let distribute = |amount| ExecuteMsg::Distribute {
+            amount: Uint128::new(amount),
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            at_height: None,
+            list: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), distribute(50)).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), distribute(60));
+        match res {
+            Err(ContractError::TimelockRequired {}) => {}
+            _ => panic!("Must return timelock required error"),
+        }
+        let msg = ExecuteMsg::QueueAction { msg: Box::new(distribute(60)) };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), ExecuteMsg::CancelQueued { id: 2 });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        execute(deps.as_mut(), env.clone(), info.clone(), ExecuteMsg::CancelQueued { id: 2 }).unwrap();
+        env.block.time = env.block.time.plus_seconds(3600);
+        let res = execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), ExecuteMsg::ExecuteQueued { id: 2 });
+        match res {
+            Err(ContractError::QueuedActionNotFound { id: 2 }) => {}
+            _ => panic!("Must return queued action not found error"),
+        }
+
+        // removing a single member is not a bulk removal
+        let remove = |whitelist: &[&str]| ExecuteMsg::Remove {
+            whitelist: whitelist.iter().map(|address| address.to_string()).collect(),
+            strict: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), remove(&["address1", "address2"]));
+        match res {
+            Err(ContractError::TimelockRequired {}) => {}
+            _ => panic!("Must return timelock required error"),
+        }
+        execute(deps.as_mut(), env.clone(), info.clone(), remove(&["address1"])).unwrap();
+
+        let msg = ExecuteMsg::QueueAction { msg: Box::new(ExecuteMsg::Increment {}) };
+        let res = execute(deps.as_mut(), env, info, msg);
+        match res {
+            Err(ContractError::NotQueueable {}) => {}
+            _ => panic!("Must return not queueable error"),
+        }
+    }
+
+    #[test]
+    fn bulk_set_whitelist_is_timelocked() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec!["address1".to_string(), "address2".to_string(), "address3".to_string()],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let timelock = TimelockConfig {
+            delay: 3600,
+            distribute_threshold: Uint128::new(100),
+            bulk_remove_threshold: 1,
+        };
+        let msg = ExecuteMsg::SetTimelock { timelock: Some(timelock) };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let set_whitelist = |members: &[&str]| ExecuteMsg::SetWhitelist {
+            members: members.iter().map(|address| address.to_string()).collect(),
+            weight: None,
+            strict: None,
+            list: None,
+        };
+        // replacing everyone drops three members
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), set_whitelist(&[]));
+        match res {
+            Err(ContractError::TimelockRequired {}) => {}
+            _ => panic!("Must return timelock required error"),
+        }
+        // dropping one is not a bulk removal, however many are added
+        let msg = set_whitelist(&["address1", "address2", "address4", "address5"]);
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::QueueAction { msg: Box::new(set_whitelist(&[])) };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(3600);
+        execute(deps.as_mut(), env, mock_info("anyone", &[]), ExecuteMsg::ExecuteQueued { id: 1 }).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetWhitelist { list: None }).unwrap();
+        let value: WhitelistResponse = from_binary(&res).unwrap();
+        assert!(value.whitelist.is_empty());
+    }
+
+    #[test]
+    fn bulk_expiry_and_group_changes_are_timelocked() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec!["address1".to_string(), "address2".to_string(), "address3".to_string()],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let timelock = TimelockConfig {
+            delay: 3600,
+            distribute_threshold: Uint128::new(100),
+            bulk_remove_threshold: 1,
+        };
+        let msg = ExecuteMsg::SetTimelock { timelock: Some(timelock) };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // ending the terms of everyone next block is a bulk removal
+        let expires = Expiration::AtHeight(mock_env().block.height + 1);
+        let add = |whitelist: &[&str]| ExecuteMsg::Add {
+            whitelist: whitelist.iter().map(|address| address.to_string()).collect(),
+            weight: None,
+            strict: None,
+            label: None,
+            expires: Some(expires),
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), add(&["address1", "address2", "address3"]));
+        match res {
+            Err(ContractError::TimelockRequired {}) => {}
+            _ => panic!("Must return timelock required error"),
+        }
+        // a single member, or new ones, are not
+        execute(deps.as_mut(), mock_env(), info.clone(), add(&["address1", "address4", "address5"])).unwrap();
+
+        // addresses are validated before they are counted
+        let msg = ExecuteMsg::SetWhitelist {
+            members: vec!["ADDRESS1".to_string(), "address2".to_string(), "address3".to_string()],
+            weight: None,
+            strict: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::Std(_)) => {}
+            _ => panic!("Must return invalid address error"),
+        }
+
+        let msg = ExecuteMsg::SetGroup { group: None, use_weights: None, list: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::TimelockRequired {}) => {}
+            _ => panic!("Must return timelock required error"),
+        }
+    }
+
+    #[test]
+    fn large_distributions_wait_for_approvals() {
+        let mut deps = mock_dependencies_with_balance(&coins(1000, "token"));
+
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec!["address1".to_string(), "address2".to_string()],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let approvers = vec!["signer1".to_string(), "signer2".to_string(), "signer3".to_string()];
+        let msg = ExecuteMsg::SetApprovers { approvers: approvers.clone(), required: 4, threshold: Uint128::new(100) };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::InvalidApprovalCount { required: 4, approvers: 3 }) => {}
+            _ => panic!("Must return invalid approval count error"),
+        }
+        let msg = ExecuteMsg::SetApprovers { approvers, required: 2, threshold: Uint128::new(100) };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // 2 * 40 runs right away, 2 * 60 waits for approval
+        let distribute = |amount| ExecuteMsg::Distribute {
+            amount: Uint128::new(amount),
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            at_height: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), distribute(40)).unwrap();
+        assert_eq!(2, res.messages.len());
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), distribute(60)).unwrap();
+        assert!(res.messages.is_empty());
+        assert!(res.attributes.contains(&attr("pending_distribution_id", "1")));
+
+        let msg = QueryMsg::PendingDistributions { start_after: None, limit: None };
+        let value: PendingDistributionsResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(1, value.distributions.len());
+        assert_eq!(Addr::unchecked("creator"), value.distributions[0].distribution.proposer);
+
+        let approve = ExecuteMsg::ApproveDistribution { id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), approve.clone());
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), mock_info("signer1", &[]), approve.clone()).unwrap();
+        assert!(res.messages.is_empty());
+        let res = execute(deps.as_mut(), mock_env(), mock_info("signer1", &[]), approve.clone());
+        match res {
+            Err(ContractError::AlreadyApproved { id: 1, .. }) => {}
+            _ => panic!("Must return already approved error"),
+        }
+        // the second approval pays out
+        let res = execute(deps.as_mut(), mock_env(), mock_info("signer3", &[]), approve.clone()).unwrap();
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(BankMsg::Send {
+                to_address: "address1".to_string(),
+                amount: coins(60, "token"),
+            })
+        );
+        let res = execute(deps.as_mut(), mock_env(), mock_info("signer2", &[]), approve);
+        match res {
+            Err(ContractError::DistributionNotFound { id: 1 }) => {}
+            _ => panic!("Must return distribution not found error"),
+        }
+
+        execute(deps.as_mut(), mock_env(), info.clone(), distribute(60)).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info("signer1", &[]), ExecuteMsg::CancelDistribution { id: 2 });
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CancelDistribution { id: 2 }).unwrap();
+        let msg = QueryMsg::PendingDistributions { start_after: None, limit: None };
+        let value: PendingDistributionsResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(value.distributions.is_empty());
+    }
+
+    #[test]
+    fn pending_distributions_follow_approver_changes() {
+        let mut deps = mock_dependencies_with_balance(&coins(1000, "token"));
+
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec!["address1".to_string(), "address2".to_string()],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let signers = |count: usize| (1..=count).map(|i| format!("signer{}", i)).collect::<Vec<_>>();
+        let msg = ExecuteMsg::SetApprovers { approvers: signers(3), required: 3, threshold: Uint128::new(100) };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let distribute = ExecuteMsg::Distribute {
+            amount: Uint128::new(60),
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            at_height: None,
+            list: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), distribute.clone()).unwrap();
+        let approve = ExecuteMsg::ApproveDistribution { id: 1 };
+        execute(deps.as_mut(), mock_env(), mock_info("signer1", &[]), approve.clone()).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("signer2", &[]), approve.clone()).unwrap();
+
+        // two approvals are now enough, and either approver can run it
+        let msg = ExecuteMsg::SetApprovers { approvers: signers(2), required: 2, threshold: Uint128::new(100) };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info("signer1", &[]), approve).unwrap();
+        assert_eq!(2, res.messages.len());
+        assert!(res.attributes.contains(&attr("executed", "true")));
+
+        // a timelock set after the proposal holds the distribution back
+        execute(deps.as_mut(), mock_env(), info.clone(), distribute).unwrap();
+        let timelock = TimelockConfig {
+            delay: 3600,
+            distribute_threshold: Uint128::new(100),
+            bulk_remove_threshold: 5,
+        };
+        let msg = ExecuteMsg::SetTimelock { timelock: Some(timelock) };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let approve = ExecuteMsg::ApproveDistribution { id: 2 };
+        execute(deps.as_mut(), mock_env(), mock_info("signer1", &[]), approve.clone()).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info("signer2", &[]), approve);
+        match res {
+            Err(ContractError::TimelockRequired {}) => {}
+            _ => panic!("Must return timelock required error"),
+        }
+
+        // turning approvals off drops what nobody can approve any more
+        let msg = ExecuteMsg::SetApprovers { approvers: vec![], required: 0, threshold: Uint128::zero() };
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::QueueAction { msg: Box::new(msg) }).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(3600);
+        let res = execute(deps.as_mut(), env, info, ExecuteMsg::ExecuteQueued { id: 1 }).unwrap();
+        assert!(res.attributes.contains(&attr("dropped_distributions", "1")));
+        let msg = QueryMsg::PendingDistributions { start_after: None, limit: None };
+        let value: PendingDistributionsResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(value.distributions.is_empty());
+    }
+
+    #[test]
+    fn approved_sweep_pays_the_balance_at_approval() {
+        let mut deps = mock_dependencies_with_balance(&coins(200, "token"));
+
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec!["address1".to_string(), "address2".to_string()],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::SetApprovers { approvers: vec!["signer1".to_string()], required: 1, threshold: Uint128::new(100) };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::DistributeBalance {
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            dust: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(res.attributes.contains(&attr("pending_distribution_id", "1")));
+
+        // more funds arrive while the sweep waits
+        let contract = mock_env().contract.address;
+        deps.querier.update_balance(contract, coins(300, "token"));
+        let msg = ExecuteMsg::ApproveDistribution { id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("signer1", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "address1".to_string(),
+                    amount: coins(150, "token"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "address2".to_string(),
+                    amount: coins(150, "token"),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn add_whitelist() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec![]  };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let addresses_to_add = vec!["address1".to_string(), "address2".to_string()];
+        let add_result = try_add_whitelist(deps.as_mut(), mock_env(), info, None, addresses_to_add.clone(), MemberTerms::default(), false);
+
+        assert!(add_result.is_ok());
+        // // beneficiary can release it
+        // let info = mock_info("anyone", &coins(2, "token"));
+        // let msg = ExecuteMsg::Increment {};
+        // let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // // should increase counter by 1
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetWhitelist { list: None }).unwrap();
+        let value: WhitelistResponse = from_binary(&res).unwrap();
+        assert_eq!(addresses_to_add, value.whitelist);
+    }
+
+    #[test]
+    fn list_members_paginates() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+
+        let whitelist: Vec<String> = (0..25).map(|i| format!("address{:02}", i)).collect();
+        let msg = InstantiateMsg { count: 17, whitelist };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = QueryMsg::ListMembers { start_after: None, limit: None, list: None };
+        let value: MemberListResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(DEFAULT_LIMIT as usize, value.members.len());
+        assert_eq!(Member { addr: "address00".to_string(), weight: 1 }, value.members[0]);
+
+        let msg = QueryMsg::ListMembers { start_after: Some("address09".to_string()), limit: Some(100), list: None };
+        let value: MemberListResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(15, value.members.len());
+        assert_eq!("address10", value.members[0].addr);
+
+        let msg = QueryMsg::IsMember { address: "address03".to_string(), list: None };
+        let value: IsMemberResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(value.is_member);
+
+        // re-adding and removing strangers leaves the count alone
+        let msg = ExecuteMsg::Add { whitelist: vec!["address03".to_string()], weight: Some(2), strict: None, label: None, expires: None, list: None };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Remove { whitelist: vec!["address03".to_string(), "stranger".to_string()], strict: None, list: None };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = QueryMsg::IsMember { address: "address03".to_string(), list: None };
+        let value: IsMemberResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(!value.is_member);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::MemberCount { list: None }).unwrap();
+        let value: MemberCountResponse = from_binary(&res).unwrap();
+        assert_eq!(24, value.count);
+    }
+
+    #[test]
+    fn whitelist_duplicates_are_skipped_or_rejected() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec!["address1".to_string()] };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let whitelist = vec!["address1".to_string(), "address2".to_string(), "address2".to_string()];
+        let msg = ExecuteMsg::Add { whitelist: whitelist.clone(), weight: None, strict: None, label: None, expires: None, list: None };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert!(res.attributes.contains(&attr("added", "address2")));
+        assert!(res.attributes.contains(&attr("skipped", "address1,address2")));
+
+        let msg = ExecuteMsg::Add { whitelist, weight: None, strict: Some(true), label: None, expires: None, list: None };
+        match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+            Err(ContractError::DuplicateMember { address }) => assert_eq!("address1", address),
+            _ => panic!("Must return duplicate member error"),
+        }
+
+        let whitelist = vec!["address2".to_string(), "address3".to_string()];
+        let msg = ExecuteMsg::Remove { whitelist: whitelist.clone(), strict: Some(true), list: None };
+        match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+            Err(ContractError::NotAMember { address }) => assert_eq!("address3", address),
+            _ => panic!("Must return not a member error"),
+        }
+
+        let msg = ExecuteMsg::Remove { whitelist, strict: None, list: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(res.attributes.contains(&attr("removed", "address2")));
+        assert!(res.attributes.contains(&attr("skipped", "address3")));
+    }
+
+    #[test]
+    fn expired_members_are_left_out() {
+        let mut deps = mock_dependencies_with_balance(&coins(100, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec!["address1".to_string()] };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let env = mock_env();
+        let expires = Expiration::AtHeight(env.block.height + 10);
+        let msg = ExecuteMsg::Add {
+            whitelist: vec!["address2".to_string()],
+            weight: None,
+            strict: None,
+            label: Some("contractor".to_string()),
+            expires: Some(expires),
+            list: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = QueryMsg::MemberDetails { address: "address2".to_string(), list: None };
+        let value: MemberDetailsResponse = from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        let member = value.member.unwrap();
+        assert_eq!(Some("contractor".to_string()), member.label);
+        assert_eq!(env.block.height, member.added_at_height);
+        assert_eq!("creator", member.added_by);
+        assert!(!value.expired);
+
+        // a term cannot start out expired
+        let msg = ExecuteMsg::Add {
+            whitelist: vec!["address3".to_string()],
+            weight: None,
+            strict: None,
+            label: None,
+            expires: Some(Expiration::AtHeight(env.block.height)),
+            list: None,
+        };
+        match execute(deps.as_mut(), env.clone(), info.clone(), msg) {
+            Err(ContractError::AlreadyExpired { .. }) => {}
+            _ => panic!("Must return already expired error"),
+        }
+
+        let mut later = mock_env();
+        later.block.height += 10;
+        let msg = QueryMsg::IsMember { address: "address2".to_string(), list: None };
+        let value: IsMemberResponse = from_binary(&query(deps.as_ref(), later.clone(), msg).unwrap()).unwrap();
+        assert!(!value.is_member);
+        let value: MemberCountResponse =
+            from_binary(&query(deps.as_ref(), later.clone(), QueryMsg::MemberCount { list: None }).unwrap()).unwrap();
+        assert_eq!(1, value.count);
+        let value: WhitelistResponse =
+            from_binary(&query(deps.as_ref(), later.clone(), QueryMsg::GetWhitelist { list: None }).unwrap()).unwrap();
+        assert_eq!(vec!["address1".to_string()], value.whitelist);
+
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::new(10),
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            at_height: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), later.clone(), info.clone(), msg).unwrap();
+        assert_eq!(1, res.messages.len());
+
+        // adding an expired member again starts a new term
+        let msg = ExecuteMsg::Add {
+            whitelist: vec!["address2".to_string()],
+            weight: None,
+            strict: Some(true),
+            label: None,
+            expires: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), later.clone(), info, msg).unwrap();
+        assert!(res.attributes.contains(&attr("added", "address2")));
+        let value: MemberCountResponse =
+            from_binary(&query(deps.as_ref(), later, QueryMsg::MemberCount { list: None }).unwrap()).unwrap();
+        assert_eq!(2, value.count);
+    }
+
+    #[test]
+    fn past_membership_is_paid_at_height() {
+        let mut deps = mock_dependencies_with_balance(&coins(100, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec!["address1".to_string()] };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let start = mock_env().block.height;
+
+        let mut env = mock_env();
+        env.block.height = start + 5;
+        let msg = ExecuteMsg::Add {
+            whitelist: vec!["address2".to_string()],
+            weight: None,
+            strict: None,
+            label: None,
+            expires: None,
+            list: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        env.block.height = start + 10;
+        let msg = ExecuteMsg::Remove { whitelist: vec!["address1".to_string()], strict: None, list: None };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let is_member_at = |deps: Deps, address: &str, height: u64| {
+            let msg = QueryMsg::IsMemberAtHeight { address: address.to_string(), height, list: None };
+            from_binary::<IsMemberResponse>(&query(deps, mock_env(), msg).unwrap()).unwrap().is_member
+        };
+        assert!(!is_member_at(deps.as_ref(), "address1", start));
+        assert!(is_member_at(deps.as_ref(), "address1", start + 10));
+        assert!(!is_member_at(deps.as_ref(), "address1", start + 11));
+        assert!(!is_member_at(deps.as_ref(), "address2", start + 5));
+        assert!(is_member_at(deps.as_ref(), "address2", start + 6));
+
+        let msg = QueryMsg::ListMembersAtHeight { height: start + 6, start_after: None, limit: None, list: None };
+        let value: MemberListResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let members: Vec<String> = value.members.into_iter().map(|member| member.addr).collect();
+        assert_eq!(vec!["address1".to_string(), "address2".to_string()], members);
+
+        env.block.height = start + 20;
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::new(10),
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            at_height: Some(start + 21),
+            list: None,
+        };
+        match execute(deps.as_mut(), env.clone(), info.clone(), msg) {
+            Err(ContractError::FutureHeight { .. }) => {}
+            _ => panic!("Must return future height error"),
+        }
+
+        // address2 was added too late and address1 is paid although it has left since
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::new(10),
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            at_height: Some(start + 3),
+            list: None,
+        };
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "address1".to_string(),
+                amount: coins(10, "token"),
+            })]
+        );
+    }
+
+    #[test]
+    fn named_lists_are_managed_and_paid_separately() {
+        let mut deps = mock_dependencies_with_balance(&coins(100, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec!["address1".to_string()] };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreateList { name: "advisors".to_string() };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::CreateList { name: "advisors".to_string() };
+        match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+            Err(ContractError::ListExists { .. }) => {}
+            _ => panic!("Must return list exists error"),
+        }
+        let msg = ExecuteMsg::CreateList { name: "team".to_string() };
+        match execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg) {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        let msg = ExecuteMsg::Add {
+            whitelist: vec!["address2".to_string(), "address3".to_string()],
+            weight: None,
+            strict: None,
+            label: None,
+            expires: None,
+            list: Some("advisors".to_string()),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::RenameList { name: "advisors".to_string(), new_name: "board".to_string() };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = QueryMsg::Lists { start_after: None, limit: None };
+        let value: ListsResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(
+            vec![
+                ListSummary { name: "board".to_string(), member_count: 2, group: None },
+                ListSummary { name: "default".to_string(), member_count: 1, group: None },
+            ],
+            value.lists
+        );
+
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::new(10),
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            at_height: None,
+            list: Some("board".to_string()),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(2, res.messages.len());
+        assert!(res.attributes.contains(&attr("list", "board")));
+
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::new(10),
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            at_height: None,
+            list: Some("advisors".to_string()),
+        };
+        match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+            Err(ContractError::ListNotFound { name }) => assert_eq!("advisors", name),
+            _ => panic!("Must return list not found error"),
+        }
+
+        let msg = ExecuteMsg::DeleteList { name: "board".to_string() };
+        match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+            Err(ContractError::ListNotEmpty { .. }) => {}
+            _ => panic!("Must return list not empty error"),
+        }
+        let msg = ExecuteMsg::Remove {
+            whitelist: vec!["address2".to_string(), "address3".to_string()],
+            strict: None,
+            list: Some("board".to_string()),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::DeleteList { name: "board".to_string() };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // the default list is there to stay
+        let msg = ExecuteMsg::RenameList { name: DEFAULT_LIST.to_string(), new_name: "main".to_string() };
+        match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+            Err(ContractError::DefaultList {}) => {}
+            _ => panic!("Must return default list error"),
+        }
+        let msg = ExecuteMsg::DeleteList { name: DEFAULT_LIST.to_string() };
+        match execute(deps.as_mut(), mock_env(), info, msg) {
+            Err(ContractError::DefaultList {}) => {}
+            _ => panic!("Must return default list error"),
+        }
+
+        // the default list was never touched
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetWhitelist { list: None }).unwrap();
+        let value: WhitelistResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["address1".to_string()], value.whitelist);
+        let msg = QueryMsg::Lists { start_after: None, limit: None };
+        let value: ListsResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(1, value.lists.len());
+    }
+
+    #[test]
+    fn deleting_a_list_clears_what_it_left_behind() {
+        let mut deps = mock_dependencies_with_balance(&coins(1000, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec![] };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::CreateList { name: "team".to_string() };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let list = Some("team".to_string());
+
+        let team: Vec<String> = (0..60).map(|i| format!("address{:02}", i)).collect();
+        let msg = ExecuteMsg::Add {
+            whitelist: team.clone(),
+            weight: None,
+            strict: None,
+            label: None,
+            expires: Some(Expiration::AtHeight(mock_env().block.height + 10)),
+            list: list.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::AddHook { addr: "hook".to_string(), list: list.clone() };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Apply { memo: None, list: list.clone() };
+        execute(deps.as_mut(), mock_env(), mock_info("applicant", &[]), msg).unwrap();
+
+        // a round still walking the team keeps the list alive
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::new(1),
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            at_height: None,
+            list: list.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Remove { whitelist: team, strict: None, list: list.clone() };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::DeleteList { name: "team".to_string() };
+        match execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()) {
+            Err(ContractError::ListInUse { name }) => assert_eq!("team", name),
+            _ => panic!("Must return list in use error"),
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), ExecuteMsg::CancelRound { round_id: 1 }).unwrap();
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let storage = deps.as_ref().storage;
+        let id = 2;
+        assert_eq!(None, HOOKS.prefix(id).keys(storage, None, None, Order::Ascending).next());
+        assert_eq!(None, APPLICATIONS.prefix(id).keys(storage, None, None, Order::Ascending).next());
+        assert_eq!(None, MEMBER_HISTORY.prefix(id).keys(storage, None, None, Order::Ascending).next());
+        assert_eq!(None, EXPIRES_AT_HEIGHT.prefix(id).keys(storage, None, None, Order::Ascending).next());
+        assert_eq!(None, LIST_TOTALS.may_load(storage, id).unwrap());
+    }
+
+    #[test]
+    fn cw4_queries_and_member_hooks() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec!["address1".to_string()] };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::AddHook { addr: "hook".to_string(), list: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg.clone());
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+        execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::HookAlreadyRegistered { .. }) => {}
+            _ => panic!("Must return hook already registered error"),
+        }
+        let value: HooksResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Hooks { list: None }).unwrap()).unwrap();
+        assert_eq!(vec!["hook".to_string()], value.hooks);
+
+        // a new member and a reweighted one are both reported
+        let msg = ExecuteMsg::Add {
+            whitelist: vec!["address1".to_string(), "address2".to_string()],
+            weight: Some(3),
+            strict: None,
+            label: None,
+            expires: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let hook_msg = MemberChangedHookMsg::new(vec![
+            MemberDiff::new("address2", None, Some(3)),
+            MemberDiff::new("address1", Some(1), Some(3)),
+        ]);
+        assert_eq!(res.messages, vec![SubMsg::new(hook_msg.into_cosmos_msg("hook").unwrap())]);
+
+        // nothing changed, so the hook is not called
+        let msg = ExecuteMsg::Add {
+            whitelist: vec!["address2".to_string()],
+            weight: None,
+            strict: None,
+            label: None,
+            expires: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert!(res.messages.is_empty());
+
+        let msg = QueryMsg::Member { addr: "address2".to_string(), at_height: None, list: None };
+        let value: MemberResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(Some(3), value.weight);
+        let value: TotalWeightResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::TotalWeight { list: None }).unwrap()).unwrap();
+        assert_eq!(6, value.weight);
+        let value: AdminResponse = from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Admin {}).unwrap()).unwrap();
+        assert_eq!(Some("creator".to_string()), value.admin);
+
+        let mut env = mock_env();
+        env.block.height += 1;
+        let msg = ExecuteMsg::Remove { whitelist: vec!["address1".to_string()], strict: None, list: None };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let hook_msg = MemberChangedHookMsg::new(vec![MemberDiff::new("address1", Some(3), None)]);
+        assert_eq!(res.messages, vec![SubMsg::new(hook_msg.into_cosmos_msg("hook").unwrap())]);
+
+        let msg = QueryMsg::Member { addr: "address1".to_string(), at_height: None, list: None };
+        let value: MemberResponse = from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(None, value.weight);
+        let msg = QueryMsg::Member { addr: "address1".to_string(), at_height: Some(env.block.height), list: None };
+        let value: MemberResponse = from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(Some(3), value.weight);
+        let value: TotalWeightResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::TotalWeight { list: None }).unwrap()).unwrap();
+        assert_eq!(3, value.weight);
+
+        let msg = ExecuteMsg::RemoveHook { addr: "hook".to_string(), list: None };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        let res = execute(deps.as_mut(), env, info, msg);
+        match res {
+            Err(ContractError::HookNotRegistered { .. }) => {}
+            _ => panic!("Must return hook not registered error"),
+        }
+    }
+
+    #[test]
+    fn applications_are_approved_or_rejected() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec!["address1".to_string()] };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        for applicant in ["address2", "address3", "address4"] {
+            let msg = ExecuteMsg::Apply { memo: Some(format!("{} here", applicant)), list: None };
+            execute(deps.as_mut(), mock_env(), mock_info(applicant, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::Apply { memo: None, list: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("address2", &[]), msg.clone());
+        match res {
+            Err(ContractError::AlreadyApplied { .. }) => {}
+            _ => panic!("Must return already applied error"),
+        }
+        let res = execute(deps.as_mut(), mock_env(), mock_info("address1", &[]), msg);
+        match res {
+            Err(ContractError::DuplicateMember { .. }) => {}
+            _ => panic!("Must return duplicate member error"),
+        }
+
+        let msg = QueryMsg::PendingApplications { start_after: None, limit: Some(2), list: None };
+        let value: PendingApplicationsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(2, value.applications.len());
+        assert_eq!("address2", value.applications[0].address);
+        assert_eq!(Some("address2 here".to_string()), value.applications[0].application.memo);
+        let msg = QueryMsg::PendingApplications {
+            start_after: Some("address3".to_string()),
+            limit: None,
+            list: None,
+        };
+        let value: PendingApplicationsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(1, value.applications.len());
+        assert_eq!("address4", value.applications[0].address);
+
+        let msg = ExecuteMsg::Approve { addresses: vec!["address2".to_string()], list: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("address2", &[]), msg.clone());
+        match res {
+            Err(ContractError::MissingRole { role }) => assert_eq!("whitelist_manager", role),
+            _ => panic!("Must return missing role error"),
+        }
+        // one applicant without an application fails the whole call
+        let msg = ExecuteMsg::Approve {
+            addresses: vec!["address2".to_string(), "address5".to_string()],
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::NoApplication { address }) => assert_eq!("address5", address),
+            _ => panic!("Must return no application error"),
+        }
+
+        let msg = ExecuteMsg::Approve { addresses: vec!["address2".to_string()], list: None };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert!(res.attributes.contains(&attr("approved", "address2")));
+        let msg = QueryMsg::IsMember { address: "address2".to_string(), list: None };
+        let value: IsMemberResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(value.is_member);
+
+        let msg = ExecuteMsg::Reject {
+            addresses: vec!["address3".to_string()],
+            reason: Some("unknown".to_string()),
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert!(res.attributes.contains(&attr("rejected", "address3")));
+        assert!(res.attributes.contains(&attr("reason", "unknown")));
+        let msg = QueryMsg::IsMember { address: "address3".to_string(), list: None };
+        let value: IsMemberResponse = from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        assert!(!value.is_member);
+
+        let msg = QueryMsg::PendingApplications { start_after: None, limit: None, list: None };
+        let value: PendingApplicationsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+        let pending: Vec<String> = value.applications.into_iter().map(|entry| entry.address).collect();
+        assert_eq!(vec!["address4".to_string()], pending);
+    }
+
+    #[test]
+    fn set_whitelist_replaces_members() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec!["address1".to_string(), "address2".to_string()],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::SetWhitelist {
+            members: vec!["address2".to_string(), "address3".to_string(), "address3".to_string()],
+            weight: None,
+            strict: Some(true),
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::DuplicateMember { address }) => assert_eq!("address3", address),
+            _ => panic!("Must return duplicate member error"),
+        }
+        // a bad address anywhere leaves the list untouched
+        let msg = ExecuteMsg::SetWhitelist {
+            members: vec!["address3".to_string(), "Address4".to_string()],
+            weight: None,
+            strict: None,
+            list: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetWhitelist { list: None }).unwrap();
+        let value: WhitelistResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["address1".to_string(), "address2".to_string()], value.whitelist);
+
+        let msg = ExecuteMsg::SetWhitelist {
+            members: vec!["address2".to_string(), "address3".to_string(), "address3".to_string()],
+            weight: None,
+            strict: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.events,
+            vec![
+                Event::new("whitelist_removed")
+                    .add_attribute("list", "default")
+                    .add_attribute("address", "address1"),
+                Event::new("whitelist_added")
+                    .add_attribute("list", "default")
+                    .add_attribute("address", "address3")
+                    .add_attribute("weight", "1"),
+            ]
+        );
+        assert!(res.attributes.contains(&attr("skipped", "address3")));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetWhitelist { list: None }).unwrap();
+        let value: WhitelistResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["address2".to_string(), "address3".to_string()], value.whitelist);
+    }
+
+    #[test]
+    fn add_whitelist_rejects_invalid_address() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec![] };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Add { whitelist: vec!["address1".to_string(), "ADDRESS2".to_string()], weight: None, strict: None, label: None, expires: None, list: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::Std(StdError::GenericErr { .. })) => {}
+            _ => panic!("Must return invalid address error"),
+        }
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetWhitelist { list: None }).unwrap();
+        let value: WhitelistResponse = from_binary(&res).unwrap();
+        assert!(value.whitelist.is_empty());
+    }
+
+    #[test]
+    fn migrate_moves_whitelist_into_members() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        legacy::STATE
+            .save(
+                deps.as_mut().storage,
+                &legacy::State {
+                    count: 3,
+                    owner: Some(Addr::unchecked("creator")),
+                    whitelist: vec!["address2".to_string(), "ADDRESS3".to_string(), "address1".to_string()],
+                },
+            )
+            .unwrap();
+        legacy::WEIGHTS.save(deps.as_mut().storage, "address2", &4).unwrap();
+
+        let res = migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        assert!(res.attributes.contains(&attr("migrated", "2")));
+        assert!(res.attributes.contains(&attr("skipped", "ADDRESS3")));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Shares { list: None }).unwrap();
+        let value: SharesResponse = from_binary(&res).unwrap();
+        assert_eq!(5, value.total_weight);
+        assert_eq!("address1", value.shares[0].address);
+        assert_eq!(4, value.shares[1].weight);
+        assert!(legacy::WEIGHTS.may_load(deps.as_ref().storage, "address2").unwrap().is_none());
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetCount {}).unwrap();
+        let value: CountResponse = from_binary(&res).unwrap();
+        assert_eq!(3, value.count);
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::MemberCount { list: None }).unwrap();
+        let value: MemberCountResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.count);
+    }
+
+    #[test]
+    fn remove_whitelist() {
+        let mut deps = mock_dependencies_with_balance(&coins(2, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec!["address1".to_string(), "address2".to_string()]  };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let addresses_to_remove = vec!["address1".to_string()];
+        let remove_result = try_remove_whitelist(deps.as_mut(), mock_env(), info, None, addresses_to_remove.clone(), false);
+
+        assert!(remove_result.is_ok());
+        // // beneficiary can release it
+        // let info = mock_info("anyone", &coins(2, "token"));
+        // let msg = ExecuteMsg::Increment {};
+        // let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // // should increase counter by 1
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetWhitelist { list: None }).unwrap();
+        let value: WhitelistResponse = from_binary(&res).unwrap();
+        assert_eq!(vec!["address2".to_string()], value.whitelist);
     }
     
     #[test]
@@ -276,54 +4026,775 @@ mod tests {
         let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg_instantiate).unwrap();
 
         let addresses_to_add = vec!["address1".to_string(), "address2".to_string()];
-        let add_result = try_add_whitelist(deps.as_mut(), info.clone(), addresses_to_add.clone());
+        let add_result = try_add_whitelist(deps.as_mut(), mock_env(), info.clone(), None, addresses_to_add.clone(), MemberTerms::default(), false);
         
         assert!(add_result.is_ok());
 
-        // log(&format!("Response: {:?}", add_result));
-        
-        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetWhitelist {}).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::GetWhitelist { list: None }).unwrap();
         let value: WhitelistResponse = from_binary(&res).unwrap();
         assert_eq!(addresses_to_add, value.whitelist);
 
-        let sender = String::from("creator");
-        let msg_distribute = ExecuteMsg::Distribute {amount: 100 , denom: "token".to_string()};
-        // let _res1 = execute(deps.as_mut(), mock_env(), info, msg_distribute).unwrap();
-        let _res1 = try_token_distribute(deps.as_ref(), mock_env(), info, 100 ,"token");
-        assert!(_res1.is_ok());
+        let msg_distribute = ExecuteMsg::Distribute {amount: Uint128::new(100) , asset: AssetInfo::native("token"), mode: None, split: None, at_height: None, list: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg_distribute).unwrap();
 
+        // one bank send per whitelisted address
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "address1".to_string(),
+                    amount: coins(100, "token"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "address2".to_string(),
+                    amount: coins(100, "token"),
+                }),
+            ]
+        );
 
+        // one transfer event per recipient, then the total per asset
+        assert_eq!(3, res.events.len());
+        assert_eq!(
+            res.events[0],
+            Event::new("distribute_transfer")
+                .add_attribute("recipient", "address1")
+                .add_attribute("asset", "token")
+                .add_attribute("amount", "100")
+        );
+        assert_eq!(
+            res.events[2],
+            Event::new("distribute_total")
+                .add_attribute("asset", "token")
+                .add_attribute("total_amount", "200")
+                .add_attribute("dust", "0")
+        );
+    }
 
-        // // beneficiary can release it
-        // let info = mock_info("anyone", &coins(2, "token"));
-        // let msg = ExecuteMsg::Increment {};
-        // let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    #[test]
+    fn token_distribute_insufficient_balance() {
+        let mut deps = mock_dependencies_with_balance(&coins(150, "token"));
+
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec!["address1".to_string(), "address2".to_string()],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
+        // 2 * 100 is more than the contract holds
+        let msg = ExecuteMsg::Distribute { amount: Uint128::new(100), asset: AssetInfo::native("token"), mode: None, split: None, at_height: None, list: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::InsufficientError {}) => {}
+            _ => panic!("Must return insufficient error"),
+        }
 
+        // only the owner may distribute
+        let msg = ExecuteMsg::Distribute { amount: Uint128::new(50), asset: AssetInfo::native("token"), mode: None, split: None, at_height: None, list: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        match res {
+            Err(ContractError::MissingRole { role }) => assert_eq!("distributor", role),
+            _ => panic!("Must return missing role error"),
+        }
+    }
 
-        // let sender1 = String::from("address2");
-        // let querier: QuerierWrapper = QuerierWrapper::new(&deps.querier);
+    #[test]
+    fn accrue_and_claim() {
+        let mut deps = mock_dependencies_with_balance(&coins(250, "token"));
 
-        // let balance_sender1 = querier.query_balance(sender1, "token").unwrap();
-        // assert_eq!(balance_sender1.amount, Uint128::new(100));
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec!["address1".to_string(), "address2".to_string()],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
 
-        // let balance_contract = querier.query_balance(mock_env().contract.address, "token").unwrap();
-        // assert_eq!(balance_contract.amount, Uint128::new(5000));
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::new(100),
+            asset: AssetInfo::native("token"),
+            mode: Some(PayoutMode::Accrue),
+            split: None,
+            at_height: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        // nothing is sent until members claim
+        assert_eq!(0, res.messages.len());
 
-        
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Claimable { address: "address1".to_string() },
+        )
+        .unwrap();
+        let value: ClaimableResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            vec![Asset { info: AssetInfo::native("token"), amount: Uint128::new(100) }],
+            value.claimable
+        );
 
-        // let querier: QuerierWrapper = deps.querier;
-        // let balance = querier.query_balance(sender, "token").unwrap();
-        // assert_eq!(balance, Coin {
-        //     denom: "token".to_string(),
-        //     amount: Uint128::new(100), // 1000 - 100
-        // });
+        // 200 of the 250 are reserved, so a second run of 2 * 100 must fail
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::new(100),
+            asset: AssetInfo::native("token"),
+            mode: Some(PayoutMode::Push),
+            split: None,
+            at_height: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::InsufficientError {}) => {}
+            _ => panic!("Must return insufficient error"),
+        }
 
-        // // should increase counter by 1
-        // let res = query(deps.as_ref(), mock_env(), QueryMsg::GetWhitelist {}).unwrap();
-        // let value: WhitelistResponse = from_binary(&res).unwrap();
-        // assert_eq!(addresses_to_add, value.whitelist);
+        let msg = ExecuteMsg::Claim { asset: AssetInfo::native("token") };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("address1", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "address1".to_string(),
+                amount: coins(100, "token"),
+            })]
+        );
+        assert_eq!(
+            Uint128::new(100),
+            RESERVED.load(deps.as_ref().storage, "native:token").unwrap()
+        );
+
+        // the ledger entry is gone after claiming
+        let msg = ExecuteMsg::Claim { asset: AssetInfo::native("token") };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("address1", &[]), msg);
+        match res {
+            Err(ContractError::NothingToClaim { .. }) => {}
+            _ => panic!("Must return nothing to claim error"),
+        }
     }
 
-    
+    #[test]
+    fn weighted_distribution() {
+        let mut deps = mock_dependencies_with_balance(&coins(1000, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec![] };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Add { whitelist: vec!["address1".to_string()], weight: Some(2), strict: None, label: None, expires: None, list: None };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        // no weight means the default weight of 1
+        let msg = ExecuteMsg::Add {
+            whitelist: vec!["address2".to_string(), "address3".to_string()],
+            weight: None,
+            strict: None,
+            label: None,
+            expires: None,
+            list: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Shares { list: None }).unwrap();
+        let value: SharesResponse = from_binary(&res).unwrap();
+        assert_eq!(4, value.total_weight);
+        assert_eq!(Decimal::percent(50), value.shares[0].share);
+        assert_eq!(Decimal::percent(25), value.shares[1].share);
+
+        // 103 split 2:1:1 is 51/25/25, and the 2 units of dust stay put
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::new(103),
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: Some(Split::Weighted),
+            at_height: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "address1".to_string(),
+                    amount: coins(51, "token"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "address2".to_string(),
+                    amount: coins(25, "token"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "address3".to_string(),
+                    amount: coins(25, "token"),
+                }),
+            ]
+        );
+        let total = res.events.iter().find(|e| e.ty == "distribute_total").unwrap();
+        assert!(total.attributes.contains(&attr("total_amount", "101")));
+        assert!(total.attributes.contains(&attr("dust", "2")));
+    }
+
+    #[test]
+    fn distribution_round_is_paid_in_pages() {
+        let mut deps = mock_dependencies_with_balance(&coins(1000, "token"));
+
+        let whitelist: Vec<String> = (0..60).map(|i| format!("address{:02}", i)).collect();
+        let msg = InstantiateMsg { count: 17, whitelist };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::new(10),
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            at_height: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(DEFAULT_BATCH_LIMIT as usize, res.messages.len());
+        assert!(res.attributes.contains(&attr("round_id", "1")));
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Round { id: 1 }).unwrap();
+        let value: RoundResponse = from_binary(&res).unwrap();
+        assert_eq!(60, value.recipients);
+        assert_eq!(50, value.processed);
+        assert_eq!(
+            vec![Asset { info: AssetInfo::native("token"), amount: Uint128::new(500) }],
+            value.paid
+        );
+        assert!(!value.complete);
+        assert_eq!(10, value.pending.len());
+        assert_eq!("address50", value.pending[0].recipient);
+
+        // the unpaid rest is reserved and cannot be distributed again
+        assert_eq!(
+            Uint128::new(100),
+            RESERVED.load(deps.as_ref().storage, "native:token").unwrap()
+        );
+
+        // anyone can crank the round
+        let msg = ExecuteMsg::ContinueDistribution { round_id: 1, limit: Some(4) };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(4, res.messages.len());
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(BankMsg::Send {
+                to_address: "address50".to_string(),
+                amount: coins(10, "token"),
+            })
+        );
+
+        let msg = ExecuteMsg::ContinueDistribution { round_id: 1, limit: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(6, res.messages.len());
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Round { id: 1 }).unwrap();
+        let value: RoundResponse = from_binary(&res).unwrap();
+        assert!(value.complete);
+        assert_eq!(
+            vec![Asset { info: AssetInfo::native("token"), amount: Uint128::new(600) }],
+            value.paid
+        );
+        assert!(value.pending.is_empty());
+        assert_eq!(
+            Uint128::zero(),
+            RESERVED.load(deps.as_ref().storage, "native:token").unwrap()
+        );
+
+        let msg = ExecuteMsg::ContinueDistribution { round_id: 1, limit: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg);
+        match res {
+            Err(ContractError::RoundComplete { round_id: 1 }) => {}
+            _ => panic!("Must return round complete error"),
+        }
+    }
+
+    #[test]
+    fn round_pays_the_membership_it_started_with() {
+        let mut deps = mock_dependencies_with_balance(&coins(1000, "token"));
+
+        let whitelist: Vec<String> = (0..60).map(|i| format!("address{:02}", i)).collect();
+        let msg = InstantiateMsg { count: 17, whitelist };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::new(600),
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: Some(Split::Even),
+            at_height: None,
+            list: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // members leaving or joining after the round started change nothing
+        let mut env = mock_env();
+        env.block.height += 1;
+        let msg = ExecuteMsg::Remove { whitelist: vec!["address55".to_string()], strict: None, list: None };
+        let _res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Add {
+            whitelist: vec!["address99".to_string()],
+            weight: None,
+            strict: None,
+            label: None,
+            expires: None,
+            list: None,
+        };
+        let _res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::ContinueDistribution { round_id: 1, limit: None };
+        let res = execute(deps.as_mut(), env, mock_info("anyone", &[]), msg).unwrap();
+        assert_eq!(10, res.messages.len());
+        assert_eq!(
+            res.messages[5],
+            SubMsg::new(BankMsg::Send {
+                to_address: "address55".to_string(),
+                amount: coins(10, "token"),
+            })
+        );
+        assert!(res.attributes.contains(&attr("remaining", "0")));
+        assert_eq!(
+            Uint128::zero(),
+            RESERVED.load(deps.as_ref().storage, "native:token").unwrap()
+        );
+    }
+
+    #[test]
+    fn owner_cancels_an_open_round() {
+        let mut deps = mock_dependencies_with_balance(&coins(1000, "token"));
+
+        let whitelist: Vec<String> = (0..60).map(|i| format!("address{:02}", i)).collect();
+        let msg = InstantiateMsg { count: 17, whitelist };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::new(10),
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            at_height: None,
+            list: None,
+        };
+        let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(
+            Uint128::new(100),
+            RESERVED.load(deps.as_ref().storage, "native:token").unwrap()
+        );
+
+        // only the owner can cancel
+        let msg = ExecuteMsg::CancelRound { round_id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg.clone());
+        match res {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Must return unauthorized error"),
+        }
+
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+        assert!(res.messages.is_empty());
+        assert!(res.attributes.contains(&attr("dropped", "10")));
+        assert_eq!(
+            Uint128::zero(),
+            RESERVED.load(deps.as_ref().storage, "native:token").unwrap()
+        );
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Round { id: 1 }).unwrap();
+        let value: RoundResponse = from_binary(&res).unwrap();
+        assert!(value.complete);
+        assert!(value.cancelled);
+        assert_eq!(50, value.processed);
+        assert!(value.pending.is_empty());
+
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::RoundComplete { round_id: 1 }) => {}
+            _ => panic!("Must return round complete error"),
+        }
+        let msg = ExecuteMsg::ContinueDistribution { round_id: 1, limit: None };
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::RoundComplete { round_id: 1 }) => {}
+            _ => panic!("Must return round complete error"),
+        }
+    }
+
+    #[test]
+    fn distribute_many_combines_coins() {
+        let mut deps = mock_dependencies_with_balance(&[coin(1000, "utoken"), coin(100, "ustake")]);
+
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec!["address1".to_string(), "address2".to_string()],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::DistributeMany {
+            assets: vec![
+                Asset { info: AssetInfo::native("utoken"), amount: Uint128::new(300) },
+                Asset { info: AssetInfo::native("ustake"), amount: Uint128::new(20) },
+            ],
+            mode: None,
+            split: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        // one send per recipient, coins sorted by denom
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "address1".to_string(),
+                    amount: vec![coin(20, "ustake"), coin(300, "utoken")],
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "address2".to_string(),
+                    amount: vec![coin(20, "ustake"), coin(300, "utoken")],
+                }),
+            ]
+        );
+
+        // ustake is short, so nothing at all is paid
+        let msg = ExecuteMsg::DistributeMany {
+            assets: vec![
+                Asset { info: AssetInfo::native("utoken"), amount: Uint128::new(10) },
+                Asset { info: AssetInfo::native("ustake"), amount: Uint128::new(100) },
+            ],
+            mode: None,
+            split: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::InsufficientError {}) => {}
+            _ => panic!("Must return insufficient error"),
+        }
+
+        let msg = ExecuteMsg::DistributeMany {
+            assets: vec![
+                Asset { info: AssetInfo::native("utoken"), amount: Uint128::new(1) },
+                Asset { info: AssetInfo::native("utoken"), amount: Uint128::new(2) },
+            ],
+            mode: None,
+            split: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::DuplicateAsset { .. }) => {}
+            _ => panic!("Must return duplicate asset error"),
+        }
+    }
+
+    #[test]
+    fn distribute_balance_sweeps_free_balance() {
+        let mut deps = mock_dependencies_with_balance(&coins(1003, "token"));
+
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec!["address1".to_string(), "address2".to_string()],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // a fixed amount per member makes no sense for a sweep
+        let msg = ExecuteMsg::DistributeBalance {
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: Some(Split::Fixed),
+            dust: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::InvalidSplit { .. }) => {}
+            _ => panic!("Must return invalid split error"),
+        }
+
+        // the swept balance is not the sender's to get the dust of
+        let msg = ExecuteMsg::DistributeBalance {
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            dust: Some(DustRule::Sender),
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+        match res {
+            Err(ContractError::InvalidDustRule { rule }) => assert_eq!("sender", rule),
+            _ => panic!("Must return invalid dust rule error"),
+        }
+
+        // owe 2 to claimants first; only the other 1001 can be swept
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::new(1),
+            asset: AssetInfo::native("token"),
+            mode: Some(PayoutMode::Accrue),
+            split: None,
+            at_height: None,
+            list: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Add { whitelist: vec!["address3".to_string()], weight: None, strict: None, label: None, expires: None, list: None };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // 1001 / 3 is 333 each, and the 2 left over go to the owner
+        let msg = ExecuteMsg::DistributeBalance {
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            dust: Some(DustRule::Owner),
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "address1".to_string(),
+                    amount: coins(333, "token"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "address2".to_string(),
+                    amount: coins(333, "token"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "address3".to_string(),
+                    amount: coins(333, "token"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "creator".to_string(),
+                    amount: coins(2, "token"),
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn distribute_balance_dust_to_first_member() {
+        let mut deps = mock_dependencies_with_balance(&coins(100, "token"));
+
+        let msg = InstantiateMsg { count: 17, whitelist: vec![] };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Add {
+            whitelist: vec!["address1".to_string(), "address2".to_string()],
+            weight: Some(1),
+            strict: None,
+            label: None,
+            expires: None,
+            list: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Add { whitelist: vec!["address3".to_string()], weight: Some(4), strict: None, label: None, expires: None, list: None };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // 100 split 1:1:4 is 16/16/66, the 2 left over go to address1
+        let msg = ExecuteMsg::DistributeBalance {
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: Some(Split::Weighted),
+            dust: Some(DustRule::FirstMember),
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(BankMsg::Send {
+                to_address: "address1".to_string(),
+                amount: coins(18, "token"),
+            })
+        );
+        let total = res.events.iter().find(|e| e.ty == "distribute_total").unwrap();
+        assert!(total.attributes.contains(&attr("total_amount", "100")));
+        assert!(total.attributes.contains(&attr("dust", "2")));
+    }
+
+    #[test]
+    fn distribute_attached_funds() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec![
+                "address1".to_string(),
+                "address2".to_string(),
+                "address3".to_string(),
+            ],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::DistributeFunds { mode: None, split: None, list: None };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("creator", &[]), msg);
+        match res {
+            Err(ContractError::NoFunds {}) => {}
+            _ => panic!("Must return no funds error"),
+        }
+
+        let msg = ExecuteMsg::DistributeFunds { mode: None, split: None, list: None };
+        let info = mock_info("creator", &[coin(10, "ustake"), coin(10, "utoken")]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::MixedFunds {}) => {}
+            _ => panic!("Must return mixed funds error"),
+        }
+
+        // the attached coins are part of the contract balance during execution
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(100, "utoken"));
+        let msg = ExecuteMsg::DistributeFunds { mode: None, split: None, list: None };
+        let info = mock_info("creator", &coins(100, "utoken"));
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // 100 / 3 is 33 each, and 1 goes back to the sender
+        assert_eq!(4, res.messages.len());
+        assert_eq!(
+            res.messages[2],
+            SubMsg::new(BankMsg::Send {
+                to_address: "address3".to_string(),
+                amount: coins(33, "utoken"),
+            })
+        );
+        assert_eq!(
+            res.messages[3],
+            SubMsg::new(BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: coins(1, "utoken"),
+            })
+        );
+    }
+
+    #[test]
+    fn attached_funds_above_the_timelock_threshold_are_refused() {
+        let mut deps = mock_dependencies_with_balance(&coins(100, "utoken"));
+
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec!["address1".to_string(), "address2".to_string()],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let timelock = TimelockConfig {
+            delay: 3600,
+            distribute_threshold: Uint128::new(50),
+            bulk_remove_threshold: 1,
+        };
+        let msg = ExecuteMsg::SetTimelock { timelock: Some(timelock) };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::DistributeFunds { mode: None, split: None, list: None };
+        let info = mock_info("creator", &coins(100, "utoken"));
+        let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+        match res {
+            Err(ContractError::AttachedFundsAboveTimelock {}) => {}
+            _ => panic!("Must return attached funds above timelock error"),
+        }
+
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(50, "utoken"));
+        let info = mock_info("creator", &coins(50, "utoken"));
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(2, res.messages.len());
+    }
+
+    #[test]
+    fn attached_funds_above_the_approval_threshold_are_refused() {
+        let mut deps = mock_dependencies_with_balance(&coins(100, "utoken"));
+
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec!["address1".to_string(), "address2".to_string()],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        let approvers = vec!["signer1".to_string(), "signer2".to_string()];
+        let msg = ExecuteMsg::SetApprovers { approvers, required: 2, threshold: Uint128::new(50) };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::DistributeFunds { mode: None, split: None, list: None };
+        let info = mock_info("creator", &coins(100, "utoken"));
+        let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+        match res {
+            Err(ContractError::AttachedFundsAboveApproval {}) => {}
+            _ => panic!("Must return attached funds above approval error"),
+        }
+        let pending = QueryMsg::PendingDistributions { start_after: None, limit: None };
+        let value: PendingDistributionsResponse =
+            from_binary(&query(deps.as_ref(), mock_env(), pending).unwrap()).unwrap();
+        assert!(value.distributions.is_empty());
+
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(50, "utoken"));
+        let info = mock_info("creator", &coins(50, "utoken"));
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(2, res.messages.len());
+    }
+
+    #[test]
+    fn distribute_overflow_is_an_error() {
+        let mut deps = mock_dependencies_with_balance(&coins(1000, "token"));
+
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec!["address1".to_string(), "address2".to_string()],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+        // 2 * u128::MAX does not fit
+        let msg = ExecuteMsg::Distribute {
+            amount: Uint128::MAX,
+            asset: AssetInfo::native("token"),
+            mode: None,
+            split: None,
+            at_height: None,
+            list: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::Overflow(_)) => {}
+            _ => panic!("Must return overflow error"),
+        }
+    }
+
+    #[test]
+    fn simulate_distribution() {
+        let mut deps = mock_dependencies_with_balance(&coins(150, "token"));
+
+        let msg = InstantiateMsg {
+            count: 17,
+            whitelist: vec!["address1".to_string(), "address2".to_string()],
+        };
+        let info = mock_info("creator", &[]);
+        let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let msg = QueryMsg::SimulateDistribution {
+            amount: Uint128::new(100),
+            asset: AssetInfo::native("token"),
+            split: None,
+            list: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: SimulationResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.payouts.len());
+        assert_eq!("address2", value.payouts[1].recipient);
+        assert_eq!(
+            vec![Asset { info: AssetInfo::native("token"), amount: Uint128::new(100) }],
+            value.payouts[1].assets
+        );
+        assert_eq!(Uint128::new(200), value.required);
+        assert_eq!(Uint128::new(150), value.balance);
+        assert!(value.insufficient);
+
+        let msg = QueryMsg::SimulateDistribution {
+            amount: Uint128::new(101),
+            asset: AssetInfo::native("token"),
+            split: Some(Split::Even),
+            list: None,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let value: SimulationResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128::new(101), value.required);
+        assert_eq!(Uint128::new(1), value.dust);
+        assert!(!value.insufficient);
+
+        // nothing was stored
+        assert!(ROUND_COUNT.may_load(deps.as_ref().storage).unwrap().is_none());
+    }
 }
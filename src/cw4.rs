@@ -0,0 +1,151 @@
+//! Wire types of the cw4 group interface, so cw3 multisigs and other cw4
+//! tooling can read this contract's membership and follow its changes. They
+//! serialize exactly like the ones in the `cw4` crate.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{to_binary, CosmosMsg, QuerierWrapper, StdResult, WasmMsg};
+
+/// Page size used when reading the members of an external group
+const GROUP_PAGE_LIMIT: u32 = 30;
+
+/// The queries every cw4 group answers
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw4QueryMsg {
+    Admin {},
+    TotalWeight {},
+    ListMembers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Member {
+        addr: String,
+        at_height: Option<u64>,
+    },
+    Hooks {},
+}
+
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Member {
+    pub addr: String,
+    pub weight: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MemberListResponse {
+    pub members: Vec<Member>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MemberResponse {
+    pub weight: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TotalWeightResponse {
+    pub weight: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AdminResponse {
+    pub admin: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HooksResponse {
+    pub hooks: Vec<String>,
+}
+
+/// A change of one member's weight. `None` means not a member.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MemberDiff {
+    pub key: String,
+    pub old: Option<u64>,
+    pub new: Option<u64>,
+}
+
+impl MemberDiff {
+    pub fn new(addr: impl Into<String>, old: Option<u64>, new: Option<u64>) -> Self {
+        MemberDiff {
+            key: addr.into(),
+            old,
+            new,
+        }
+    }
+}
+
+/// Sent to every registered hook when membership changes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MemberChangedHookMsg {
+    pub diffs: Vec<MemberDiff>,
+}
+
+impl MemberChangedHookMsg {
+    pub fn new(diffs: Vec<MemberDiff>) -> Self {
+        MemberChangedHookMsg { diffs }
+    }
+
+    /// Message delivering this to the hook contract `contract_addr`
+    pub fn into_cosmos_msg(self, contract_addr: impl Into<String>) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg: to_binary(&MemberChangedExecuteMsg::MemberChangedHook(self))?,
+            funds: vec![],
+        }
+        .into())
+    }
+}
+
+/// How hook contracts receive a `MemberChangedHookMsg`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberChangedExecuteMsg {
+    MemberChangedHook(MemberChangedHookMsg),
+}
+
+/// Every member of the cw4 group `group`, read page by page. Paging stops at
+/// the first empty page, so groups capping the page size below ours are read
+/// in full as well.
+pub fn query_all_members(querier: &QuerierWrapper, group: &str) -> StdResult<Vec<Member>> {
+    let mut members: Vec<Member> = vec![];
+    loop {
+        let msg = Cw4QueryMsg::ListMembers {
+            start_after: members.last().map(|member| member.addr.clone()),
+            limit: Some(GROUP_PAGE_LIMIT),
+        };
+        let page: MemberListResponse = querier.query_wasm_smart(group, &msg)?;
+        if page.members.is_empty() {
+            return Ok(members);
+        }
+        members.extend(page.members);
+    }
+}
+
+/// Up to `limit` members of the cw4 group `group` after `start_after`, and
+/// whether they are the last ones
+pub fn query_members_after(
+    querier: &QuerierWrapper,
+    group: &str,
+    start_after: Option<String>,
+    limit: usize,
+) -> StdResult<(Vec<Member>, bool)> {
+    let mut members: Vec<Member> = vec![];
+    // one member past `limit` tells whether any are left
+    while members.len() <= limit {
+        let msg = Cw4QueryMsg::ListMembers {
+            start_after: members.last().map(|member| member.addr.clone()).or_else(|| start_after.clone()),
+            limit: Some(GROUP_PAGE_LIMIT),
+        };
+        let page: MemberListResponse = querier.query_wasm_smart(group, &msg)?;
+        if page.members.is_empty() {
+            break;
+        }
+        members.extend(page.members);
+    }
+    let last = members.len() <= limit;
+    members.truncate(limit);
+    Ok((members, last))
+}
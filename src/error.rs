@@ -1,17 +1,146 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{OverflowError, StdError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum ContractError {
     #[error("{0}")]
-    Std(#[from] StdError),
+    Std(StdError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
 
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("Contract is not paused")]
+    NotPaused {},
+
+    #[error("Action has to be queued and wait out the timelock")]
+    TimelockRequired {},
+
+    #[error("No timelock is configured")]
+    NoTimelock {},
+
+    #[error("Action cannot be queued")]
+    NotQueueable {},
+
+    #[error("Attached funds above the timelock threshold cannot be queued; fund the contract and queue a Distribute instead")]
+    AttachedFundsAboveTimelock {},
+
+    #[error("Queued action {id} does not exist")]
+    QueuedActionNotFound { id: u64 },
+
+    #[error("Queued action {id} cannot run before {ready_at}")]
+    TimelockNotExpired { id: u64, ready_at: String },
+
+    #[error("Approval needs between 1 and {approvers} approvals, not {required}")]
+    InvalidApprovalCount { required: u32, approvers: u32 },
+
+    #[error("Pending distribution {id} does not exist")]
+    DistributionNotFound { id: u64 },
+
+    #[error("Pending distribution {id} is already approved by {approver}")]
+    AlreadyApproved { id: u64, approver: String },
+
+    #[error("Attached funds above the approval threshold cannot wait for approvals; fund the contract and distribute instead")]
+    AttachedFundsAboveApproval {},
+
+    #[error("Unauthorized: requires the {role} role")]
+    MissingRole { role: String },
+
+    #[error("{address} already has the {role} role")]
+    RoleAlreadyGranted { role: String, address: String },
+
+    #[error("{address} does not have the {role} role")]
+    RoleNotGranted { role: String, address: String },
+
     #[error("Insufficient Error")]
     InsufficientError {},
 
+    #[error("Nothing to claim for asset {asset}")]
+    NothingToClaim { asset: String },
+
+    #[error("Whitelist has no weight to split a distribution by")]
+    ZeroTotalWeight {},
+
+    #[error("Distribution round {round_id} is already complete")]
+    RoundComplete { round_id: u64 },
+
+    #[error("No assets to distribute")]
+    NoAssets {},
+
+    #[error("Asset {asset} is listed more than once")]
+    DuplicateAsset { asset: String },
+
+    #[error("{address} is already a member")]
+    DuplicateMember { address: String },
+
+    #[error("{address} is not a member")]
+    NotAMember { address: String },
+
+    #[error("{expires} has already passed")]
+    AlreadyExpired { expires: String },
+
+    #[error("Height {height} is in the future")]
+    FutureHeight { height: u64 },
+
+    #[error("List {name} does not exist")]
+    ListNotFound { name: String },
+
+    #[error("List {name} already exists")]
+    ListExists { name: String },
+
+    #[error("List {name} still has members")]
+    ListNotEmpty { name: String },
+
+    #[error("List {name} is still being paid out by an open round")]
+    ListInUse { name: String },
+
+    #[error("The default list cannot be renamed or deleted")]
+    DefaultList {},
+
+    #[error("Invalid list name {name:?}")]
+    InvalidListName { name: String },
+
+    #[error("Hook {hook} is already registered")]
+    HookAlreadyRegistered { hook: String },
+
+    #[error("Hook {hook} is not registered")]
+    HookNotRegistered { hook: String },
+
+    #[error("List {name} takes its members from a cw4 group, which cannot be read at a past height")]
+    GroupAtHeight { name: String },
+
+    #[error("{address} has already applied")]
+    AlreadyApplied { address: String },
+
+    #[error("{address} has not applied")]
+    NoApplication { address: String },
+
+    #[error("Memo is longer than {max} bytes")]
+    MemoTooLong { max: usize },
+
+    #[error("No ownership transfer is pending")]
+    NoPendingOwner {},
+
+    #[error("Ownership transfer has expired")]
+    OwnershipProposalExpired {},
+
+    #[error("Split {split} cannot be used here")]
+    InvalidSplit { split: String },
+
+    #[error("Dust rule {rule} cannot be used here")]
+    InvalidDustRule { rule: String },
+
+    #[error("No funds sent")]
+    NoFunds {},
+
+    #[error("Send exactly one denom")]
+    MixedFunds {},
+
     #[error("Test Error")]
     TestError {},
 
@@ -20,3 +149,13 @@ pub enum ContractError {
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
+
+impl From<StdError> for ContractError {
+    fn from(err: StdError) -> Self {
+        match err {
+            // overflows caught inside storage helpers surface as their own variant
+            StdError::Overflow { source, .. } => ContractError::Overflow(source),
+            err => ContractError::Std(err),
+        }
+    }
+}
@@ -68,4 +68,604 @@ mod tests {
             app.execute(Addr::unchecked(USER), cosmos_msg).unwrap();
         }
     }
+
+    mod distribute {
+        use super::*;
+        use crate::asset::AssetInfo;
+        use crate::msg::{ExecuteMsg, PayoutMode};
+        use crate::ContractError;
+
+        const RECIPIENT1: &str = "recipient1";
+        const RECIPIENT2: &str = "recipient2";
+
+        pub fn fund_contract(app: &mut App, contract: &CwTemplateContract, amount: u128) {
+            app.sudo(cw_multi_test::SudoMsg::Bank(cw_multi_test::BankSudo::Mint {
+                to_address: contract.addr().to_string(),
+                amount: vec![Coin {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(amount),
+                }],
+            }))
+            .unwrap();
+        }
+
+        pub fn balance(app: &App, address: &str) -> Uint128 {
+            app.wrap()
+                .query_balance(address, NATIVE_DENOM)
+                .unwrap()
+                .amount
+        }
+
+        #[test]
+        fn distribute_pays_whitelist() {
+            let (mut app, cw_template_contract) = proper_instantiate();
+            fund_contract(&mut app, &cw_template_contract, 1000);
+
+            let msg = ExecuteMsg::Add {
+                whitelist: vec![RECIPIENT1.to_string(), RECIPIENT2.to_string()],
+                weight: None,
+                strict: None,
+                label: None,
+                expires: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            assert_eq!(Uint128::zero(), balance(&app, RECIPIENT1));
+            assert_eq!(Uint128::zero(), balance(&app, RECIPIENT2));
+
+            let msg = ExecuteMsg::Distribute {
+                amount: Uint128::new(300),
+                asset: AssetInfo::native(NATIVE_DENOM),
+                mode: None,
+                split: None,
+                at_height: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            let res = app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            assert_eq!(Uint128::new(300), balance(&app, RECIPIENT1));
+            assert_eq!(Uint128::new(300), balance(&app, RECIPIENT2));
+            assert_eq!(
+                Uint128::new(400),
+                balance(&app, cw_template_contract.addr().as_str())
+            );
+
+            let transfers: Vec<_> = res
+                .events
+                .iter()
+                .filter(|e| e.ty == "wasm-distribute_transfer")
+                .collect();
+            assert_eq!(2, transfers.len());
+        }
+
+        #[test]
+        fn distribute_insufficient_balance_moves_nothing() {
+            let (mut app, cw_template_contract) = proper_instantiate();
+            fund_contract(&mut app, &cw_template_contract, 100);
+
+            let msg = ExecuteMsg::Add {
+                whitelist: vec![RECIPIENT1.to_string(), RECIPIENT2.to_string()],
+                weight: None,
+                strict: None,
+                label: None,
+                expires: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            let msg = ExecuteMsg::Distribute {
+                amount: Uint128::new(60),
+                asset: AssetInfo::native(NATIVE_DENOM),
+                mode: None,
+                split: None,
+                at_height: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            let err = app
+                .execute(Addr::unchecked(ADMIN), cosmos_msg)
+                .unwrap_err();
+            assert_eq!(
+                ContractError::InsufficientError {}.to_string(),
+                err.root_cause().to_string()
+            );
+
+            assert_eq!(Uint128::zero(), balance(&app, RECIPIENT1));
+            assert_eq!(
+                Uint128::new(100),
+                balance(&app, cw_template_contract.addr().as_str())
+            );
+        }
+
+        #[test]
+        fn accrued_funds_are_claimed_by_members() {
+            let (mut app, cw_template_contract) = proper_instantiate();
+            fund_contract(&mut app, &cw_template_contract, 1000);
+
+            let msg = ExecuteMsg::Add {
+                whitelist: vec![RECIPIENT1.to_string(), RECIPIENT2.to_string()],
+                weight: None,
+                strict: None,
+                label: None,
+                expires: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            let msg = ExecuteMsg::Distribute {
+                amount: Uint128::new(300),
+                asset: AssetInfo::native(NATIVE_DENOM),
+                mode: Some(PayoutMode::Accrue),
+                split: None,
+                at_height: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            // credited, but nothing moved yet
+            assert_eq!(Uint128::zero(), balance(&app, RECIPIENT1));
+            assert_eq!(
+                Uint128::new(1000),
+                balance(&app, cw_template_contract.addr().as_str())
+            );
+
+            let msg = ExecuteMsg::Claim {
+                asset: AssetInfo::native(NATIVE_DENOM),
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(RECIPIENT1), cosmos_msg)
+                .unwrap();
+
+            assert_eq!(Uint128::new(300), balance(&app, RECIPIENT1));
+            assert_eq!(Uint128::zero(), balance(&app, RECIPIENT2));
+            assert_eq!(
+                Uint128::new(700),
+                balance(&app, cw_template_contract.addr().as_str())
+            );
+        }
+
+        #[test]
+        fn large_round_is_cranked_to_completion() {
+            let (mut app, cw_template_contract) = proper_instantiate();
+            fund_contract(&mut app, &cw_template_contract, 1000);
+
+            let whitelist: Vec<String> = (0..75).map(|i| format!("member{:02}", i)).collect();
+            let msg = ExecuteMsg::Add {
+                whitelist,
+                weight: None,
+                strict: None,
+                label: None,
+                expires: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            let msg = ExecuteMsg::Distribute {
+                amount: Uint128::new(10),
+                asset: AssetInfo::native(NATIVE_DENOM),
+                mode: None,
+                split: None,
+                at_height: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            // the first page is paid by the distribution itself
+            assert_eq!(Uint128::new(10), balance(&app, "member00"));
+            assert_eq!(Uint128::zero(), balance(&app, "member74"));
+
+            let msg = ExecuteMsg::ContinueDistribution {
+                round_id: 1,
+                limit: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(USER), cosmos_msg).unwrap();
+
+            assert_eq!(Uint128::new(10), balance(&app, "member74"));
+            assert_eq!(
+                Uint128::new(250),
+                balance(&app, cw_template_contract.addr().as_str())
+            );
+        }
+    }
+
+    mod cw20_distribute {
+        use super::*;
+        use crate::asset::AssetInfo;
+        use crate::msg::{ExecuteMsg, ReceiveMsg};
+        use cosmwasm_std::{
+            to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+        };
+        use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+        use cw_storage_plus::Map;
+        use serde::{Deserialize, Serialize};
+
+        const RECIPIENT1: &str = "recipient1";
+        const RECIPIENT2: &str = "recipient2";
+
+        /// Bare-bones CW20: balances, Transfer, Send and the Balance query
+        mod mock_cw20 {
+            use super::*;
+
+            const BALANCES: Map<&Addr, Uint128> = Map::new("balance");
+
+            #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+            pub struct InstantiateMsg {
+                pub initial_balances: Vec<Cw20Coin>,
+            }
+
+            pub fn instantiate(
+                deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                msg: InstantiateMsg,
+            ) -> StdResult<Response> {
+                for coin in msg.initial_balances {
+                    let address = Addr::unchecked(coin.address);
+                    BALANCES.save(deps.storage, &address, &coin.amount)?;
+                }
+                Ok(Response::new())
+            }
+
+            fn move_tokens(
+                deps: DepsMut,
+                from: &Addr,
+                to: &str,
+                amount: Uint128,
+            ) -> StdResult<()> {
+                let to = deps.api.addr_validate(to)?;
+                BALANCES.update(deps.storage, from, |balance| -> StdResult<_> {
+                    Ok(balance.unwrap_or_default().checked_sub(amount)?)
+                })?;
+                BALANCES.update(deps.storage, &to, |balance| -> StdResult<_> {
+                    Ok(balance.unwrap_or_default() + amount)
+                })?;
+                Ok(())
+            }
+
+            pub fn execute(
+                deps: DepsMut,
+                _env: Env,
+                info: MessageInfo,
+                msg: Cw20ExecuteMsg,
+            ) -> StdResult<Response> {
+                match msg {
+                    Cw20ExecuteMsg::Transfer { recipient, amount } => {
+                        move_tokens(deps, &info.sender, &recipient, amount)?;
+                        Ok(Response::new())
+                    }
+                    Cw20ExecuteMsg::Send {
+                        contract,
+                        amount,
+                        msg,
+                    } => {
+                        move_tokens(deps, &info.sender, &contract, amount)?;
+                        let receive = Cw20ReceiveMsg {
+                            sender: info.sender.into(),
+                            amount,
+                            msg,
+                        };
+                        Ok(Response::new().add_message(receive.into_cosmos_msg(contract)?))
+                    }
+                    _ => Err(StdError::generic_err("not supported by mock cw20")),
+                }
+            }
+
+            pub fn query(deps: Deps, _env: Env, msg: Cw20QueryMsg) -> StdResult<Binary> {
+                match msg {
+                    Cw20QueryMsg::Balance { address } => {
+                        let address = deps.api.addr_validate(&address)?;
+                        let balance = BALANCES
+                            .may_load(deps.storage, &address)?
+                            .unwrap_or_default();
+                        to_binary(&BalanceResponse { balance })
+                    }
+                    _ => Err(StdError::generic_err("not supported by mock cw20")),
+                }
+            }
+        }
+
+        fn instantiate_cw20(app: &mut App) -> Addr {
+            let code_id = app.store_code(Box::new(ContractWrapper::new(
+                mock_cw20::execute,
+                mock_cw20::instantiate,
+                mock_cw20::query,
+            )));
+            let msg = mock_cw20::InstantiateMsg {
+                initial_balances: vec![Cw20Coin {
+                    address: ADMIN.to_string(),
+                    amount: Uint128::new(5000),
+                }],
+            };
+            app.instantiate_contract(code_id, Addr::unchecked(ADMIN), &msg, &[], "cw20", None)
+                .unwrap()
+        }
+
+        fn cw20_balance(app: &App, token: &Addr, address: &str) -> Uint128 {
+            let res: BalanceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    token,
+                    &Cw20QueryMsg::Balance {
+                        address: address.to_string(),
+                    },
+                )
+                .unwrap();
+            res.balance
+        }
+
+        #[test]
+        fn distribute_cw20_from_received_funds() {
+            let (mut app, cw_template_contract) = proper_instantiate();
+            let token = instantiate_cw20(&mut app);
+
+            // fund the contract through the receive hook
+            let msg = Cw20ExecuteMsg::Send {
+                contract: cw_template_contract.addr().into(),
+                amount: Uint128::new(1000),
+                msg: to_binary(&ReceiveMsg::Fund {}).unwrap(),
+            };
+            app.execute_contract(Addr::unchecked(ADMIN), token.clone(), &msg, &[])
+                .unwrap();
+            assert_eq!(
+                Uint128::new(1000),
+                cw20_balance(&app, &token, cw_template_contract.addr().as_str())
+            );
+
+            let msg = ExecuteMsg::Add {
+                whitelist: vec![RECIPIENT1.to_string(), RECIPIENT2.to_string()],
+                weight: None,
+                strict: None,
+                label: None,
+                expires: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            let msg = ExecuteMsg::Distribute {
+                amount: Uint128::new(400),
+                asset: AssetInfo::cw20(token.as_str()),
+                mode: None,
+                split: None,
+                at_height: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            assert_eq!(Uint128::new(400), cw20_balance(&app, &token, RECIPIENT1));
+            assert_eq!(Uint128::new(400), cw20_balance(&app, &token, RECIPIENT2));
+            assert_eq!(
+                Uint128::new(200),
+                cw20_balance(&app, &token, cw_template_contract.addr().as_str())
+            );
+
+            // the remaining 200 cannot cover another 2 * 400
+            let msg = ExecuteMsg::Distribute {
+                amount: Uint128::new(400),
+                asset: AssetInfo::cw20(token.as_str()),
+                mode: None,
+                split: None,
+                at_height: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg)
+                .unwrap_err();
+        }
+    }
+    mod group_distribute {
+        use super::distribute::{balance, fund_contract};
+        use super::*;
+        use crate::asset::AssetInfo;
+        use crate::cw4::{Cw4QueryMsg, Member, MemberListResponse, TotalWeightResponse};
+        use crate::msg::{ExecuteMsg, Split};
+        use crate::ContractError;
+        use cosmwasm_std::{
+            to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult,
+        };
+        use cw_storage_plus::{Bound, Map};
+        use serde::{Deserialize, Serialize};
+
+        /// Bare-bones cw4 group: a fixed member set and the ListMembers and
+        /// TotalWeight queries
+        mod mock_group {
+            use super::*;
+
+            const MEMBERS: Map<&str, u64> = Map::new("members");
+            // smaller than the contract's page size, to exercise paging
+            const MAX_LIMIT: u32 = 2;
+
+            #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+            pub struct InstantiateMsg {
+                pub members: Vec<Member>,
+            }
+
+            pub fn instantiate(
+                deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                msg: InstantiateMsg,
+            ) -> StdResult<Response> {
+                for member in msg.members {
+                    MEMBERS.save(deps.storage, &member.addr, &member.weight)?;
+                }
+                Ok(Response::new())
+            }
+
+            pub fn execute(
+                _deps: DepsMut,
+                _env: Env,
+                _info: MessageInfo,
+                _msg: Empty,
+            ) -> StdResult<Response> {
+                Err(StdError::generic_err("not supported by mock group"))
+            }
+
+            pub fn query(deps: Deps, _env: Env, msg: Cw4QueryMsg) -> StdResult<Binary> {
+                match msg {
+                    Cw4QueryMsg::ListMembers { start_after, limit } => {
+                        let limit = limit.unwrap_or(MAX_LIMIT).min(MAX_LIMIT) as usize;
+                        let start = start_after.as_deref().map(Bound::exclusive);
+                        let members = MEMBERS
+                            .range(deps.storage, start, None, Order::Ascending)
+                            .take(limit)
+                            .map(|item| item.map(|(addr, weight)| Member { addr, weight }))
+                            .collect::<StdResult<_>>()?;
+                        to_binary(&MemberListResponse { members })
+                    }
+                    Cw4QueryMsg::TotalWeight {} => {
+                        let weight = MEMBERS
+                            .range(deps.storage, None, None, Order::Ascending)
+                            .try_fold(0, |total, item| item.map(|(_, weight)| total + weight))?;
+                        to_binary(&TotalWeightResponse { weight })
+                    }
+                    _ => Err(StdError::generic_err("not supported by mock group")),
+                }
+            }
+        }
+
+        fn instantiate_group(app: &mut App, weights: &[u64]) -> Addr {
+            let code_id = app.store_code(Box::new(ContractWrapper::new(
+                mock_group::execute,
+                mock_group::instantiate,
+                mock_group::query,
+            )));
+            let members = weights
+                .iter()
+                .enumerate()
+                .map(|(i, weight)| Member { addr: format!("member{}", i), weight: *weight })
+                .collect();
+            let msg = mock_group::InstantiateMsg { members };
+            app.instantiate_contract(code_id, Addr::unchecked(ADMIN), &msg, &[], "group", None)
+                .unwrap()
+        }
+
+        #[test]
+        fn distribute_pays_group_members() {
+            let (mut app, cw_template_contract) = proper_instantiate();
+            fund_contract(&mut app, &cw_template_contract, 1000);
+            let group = instantiate_group(&mut app, &[1, 1, 1, 2, 5]);
+
+            // local members are not paid while the list follows the group
+            let msg = ExecuteMsg::Add {
+                whitelist: vec!["local".to_string()],
+                weight: None,
+                strict: None,
+                label: None,
+                expires: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            let msg = ExecuteMsg::SetGroup {
+                group: Some(group.to_string()),
+                use_weights: Some(true),
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            let msg = ExecuteMsg::Distribute {
+                amount: Uint128::new(100),
+                asset: AssetInfo::native(NATIVE_DENOM),
+                mode: None,
+                split: Some(Split::Weighted),
+                at_height: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            assert_eq!(Uint128::new(10), balance(&app, "member0"));
+            assert_eq!(Uint128::new(20), balance(&app, "member3"));
+            assert_eq!(Uint128::new(50), balance(&app, "member4"));
+            assert_eq!(Uint128::zero(), balance(&app, "local"));
+
+            // without the group's weights every member gets the same share
+            let msg = ExecuteMsg::SetGroup {
+                group: Some(group.to_string()),
+                use_weights: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            let msg = ExecuteMsg::Distribute {
+                amount: Uint128::new(100),
+                asset: AssetInfo::native(NATIVE_DENOM),
+                mode: None,
+                split: Some(Split::Weighted),
+                at_height: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            assert_eq!(Uint128::new(30), balance(&app, "member0"));
+            assert_eq!(Uint128::new(70), balance(&app, "member4"));
+
+            // the group keeps no history, so past heights cannot be paid
+            let msg = ExecuteMsg::Distribute {
+                amount: Uint128::new(10),
+                asset: AssetInfo::native(NATIVE_DENOM),
+                mode: None,
+                split: None,
+                at_height: Some(app.block_info().height),
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            let err = app
+                .execute(Addr::unchecked(ADMIN), cosmos_msg)
+                .unwrap_err();
+            assert_eq!(
+                ContractError::GroupAtHeight { name: "default".to_string() }.to_string(),
+                err.root_cause().to_string()
+            );
+
+            // switching back pays the list's own members again
+            let msg = ExecuteMsg::SetGroup { group: None, use_weights: None, list: None };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            let msg = ExecuteMsg::Distribute {
+                amount: Uint128::new(10),
+                asset: AssetInfo::native(NATIVE_DENOM),
+                mode: None,
+                split: None,
+                at_height: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg).unwrap();
+
+            assert_eq!(Uint128::new(10), balance(&app, "local"));
+            assert_eq!(Uint128::new(30), balance(&app, "member0"));
+        }
+
+        #[test]
+        fn set_group_rejects_non_group_contract() {
+            let (mut app, cw_template_contract) = proper_instantiate();
+
+            // an address that is not a contract cannot answer the cw4 queries
+            let msg = ExecuteMsg::SetGroup {
+                group: Some("nobody".to_string()),
+                use_weights: None,
+                list: None,
+            };
+            let cosmos_msg = cw_template_contract.call(msg).unwrap();
+            app.execute(Addr::unchecked(ADMIN), cosmos_msg)
+                .unwrap_err();
+        }
+    }
 }
@@ -1,4 +1,6 @@
+pub mod asset;
 pub mod contract;
+pub mod cw4;
 mod error;
 pub mod helpers;
 pub mod integration_tests;
@@ -1,3 +1,14 @@
+use std::fmt;
+
+use cosmwasm_std::{Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
+use cw_utils::Expiration;
+
+use crate::asset::{Asset, AssetInfo};
+use crate::state::{
+    ApprovalConfig, Application, MemberInfo, PauseInfo, Payout, PendingDistribution, QueuedAction,
+    TimelockConfig,
+};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -7,14 +18,309 @@ pub struct InstantiateMsg {
     pub whitelist:  Vec<String>
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     Increment {},
     Reset { count: i32 },
-    Add { whitelist:  Vec<String> },
-    Remove { whitelist:  Vec<String> },
-    Distribute { amount: u128 , denom: String },
+    /// Stops counter increments, membership changes and payouts until
+    /// `Unpause`. Claims keep working. Needs the pauser role.
+    Pause {},
+    /// Lifts a `Pause`; needs the pauser role
+    Unpause {},
+    /// Makes `Reset`, removals of more than `bulk_remove_threshold` addresses
+    /// and distributions needing more than `distribute_threshold` of an asset
+    /// wait `delay` seconds in the queue. `None` turns the timelock off. Once
+    /// a timelock is set, changing it has to be queued as well. Owner only.
+    SetTimelock { timelock: Option<TimelockConfig> },
+    /// Queues `msg` to run once the timelock delay has passed. Needs the same
+    /// role as sending `msg` directly.
+    QueueAction { msg: Box<ExecuteMsg> },
+    /// Runs a queued action whose delay has passed, on behalf of whoever
+    /// queued it. Anyone may send it.
+    ExecuteQueued { id: u64 },
+    /// Drops a queued action; owner only
+    CancelQueued { id: u64 },
+    /// Makes distributions needing more than `threshold` of any asset wait
+    /// until `required` of `approvers` have approved them. An empty list of
+    /// approvers turns approvals off. Owner only, and timelocked like
+    /// `SetTimelock` once a timelock is set.
+    SetApprovers {
+        approvers: Vec<String>,
+        required: u32,
+        threshold: Uint128,
+    },
+    /// Approves pending distribution `id`. The approval that reaches the
+    /// required count also runs the distribution.
+    ApproveDistribution { id: u64 },
+    /// Drops a pending distribution; owner only
+    CancelDistribution { id: u64 },
+    /// Gives `role` to `address`; owner only
+    GrantRole { role: Role, address: String },
+    /// Takes `role` away from `address`; owner only
+    RevokeRole { role: Role, address: String },
+    /// Offers ownership to `new_owner`, who has to accept it before `expiry`.
+    /// A new proposal replaces the pending one.
+    ProposeOwner {
+        new_owner: String,
+        expiry: Option<Expiration>,
+    },
+    /// Completes the pending transfer; only the proposed owner may send it
+    AcceptOwnership {},
+    /// Withdraws the pending transfer
+    CancelOwnershipProposal {},
+    /// Leaves the contract without an owner for good. Owner-only actions can
+    /// no longer be run by anyone.
+    RenounceOwnership {},
+    /// Creates an empty whitelist called `name`
+    CreateList { name: String },
+    /// Renames list `name` to `new_name`
+    RenameList { name: String, new_name: String },
+    /// Deletes list `name`, which must have no members left
+    DeleteList { name: String },
+    /// Registers contract `addr` to be sent a cw4 `MemberChangedHookMsg`
+    /// whenever `Add` or `Remove` changes list `list`, or the default list
+    AddHook { addr: String, list: Option<String> },
+    /// Unregisters a hook added with `AddHook`
+    RemoveHook { addr: String, list: Option<String> },
+    /// Makes distributions to list `list` pay the members of the cw4 group
+    /// contract `group` instead of the list's own members, which are kept but
+    /// no longer paid. `None` switches back. With `use_weights` set, weighted
+    /// splits follow the group's weights; otherwise every member counts 1.
+    SetGroup {
+        group: Option<String>,
+        use_weights: Option<bool>,
+        list: Option<String>,
+    },
+    /// Adds `whitelist` to list `list`, or to the default list when none is
+    /// named. `weight` applies to every address in the call and defaults to 1.
+    /// With `strict` set, addresses that are already members are an error
+    /// instead of being skipped. Members stop being paid once `expires` has
+    /// passed.
+    Add {
+        whitelist: Vec<String>,
+        weight: Option<u64>,
+        strict: Option<bool>,
+        label: Option<String>,
+        expires: Option<Expiration>,
+        list: Option<String>,
+    },
+    /// Removes members from list `list`, or from the default list. With
+    /// `strict` set, addresses that are not members are an error instead of
+    /// being skipped.
+    Remove { whitelist:  Vec<String>, strict: Option<bool>, list: Option<String> },
+    /// Replaces the members of list `list`, or of the default list, with
+    /// `members` in one step. Addresses not in `members` are removed, new ones
+    /// are added with `weight`, and members that stay take `weight` when it
+    /// is given. Repeated addresses are skipped, or an error with `strict`.
+    SetWhitelist {
+        members: Vec<String>,
+        weight: Option<u64>,
+        strict: Option<bool>,
+        list: Option<String>,
+    },
+    /// Asks to join list `list`, or the default list. The application stays
+    /// pending until the owner approves or rejects it.
+    Apply { memo: Option<String>, list: Option<String> },
+    /// Adds the applicants `addresses` to list `list` with the default weight.
+    /// Applicants that became members in the meantime are skipped.
+    Approve { addresses: Vec<String>, list: Option<String> },
+    /// Drops the applications of `addresses`, recording `reason` in the events
+    Reject {
+        addresses: Vec<String>,
+        reason: Option<String>,
+        list: Option<String>,
+    },
+    /// Distributes `amount` of `asset` across list `list`, or the default list.
+    /// `mode` defaults to `push` and `split` defaults to `fixed`. The payouts
+    /// are stored as a round, and the first page of it is paid right away.
+    /// With `at_height` set, the list is paid as it stood at the start of that
+    /// block, so members added just before the payout are left out.
+    Distribute {
+        amount: Uint128,
+        asset: AssetInfo,
+        mode: Option<PayoutMode>,
+        split: Option<Split>,
+        at_height: Option<u64>,
+        list: Option<String>,
+    },
+    /// Distributes several assets in one round. Each recipient's native coins
+    /// are combined into one bank send. Nothing is paid unless the contract can
+    /// cover every asset. `amount` of each asset and `list` are read like in
+    /// `Distribute`.
+    DistributeMany {
+        assets: Vec<Asset>,
+        mode: Option<PayoutMode>,
+        split: Option<Split>,
+        list: Option<String>,
+    },
+    /// Distributes the contract's whole free balance of `asset`, i.e. what is
+    /// not owed to claimants or open rounds. `split` defaults to `even` and
+    /// `fixed` is rejected; `dust` defaults to `retain`.
+    DistributeBalance {
+        asset: AssetInfo,
+        mode: Option<PayoutMode>,
+        split: Option<Split>,
+        dust: Option<DustRule>,
+        list: Option<String>,
+    },
+    /// Splits the single native coin sent with the call across the whitelist.
+    /// `split` defaults to `even` and `fixed` is rejected. The rounding
+    /// remainder is refunded to the sender. Amounts above the timelock or
+    /// approval threshold are refused, since attached funds cannot wait.
+    DistributeFunds {
+        mode: Option<PayoutMode>,
+        split: Option<Split>,
+        list: Option<String>,
+    },
+    /// Pays the next page of an open distribution round. Anyone may call this.
+    ContinueDistribution { round_id: u64, limit: Option<u32> },
+    /// Drops the outstanding payouts of an open round and releases their
+    /// reserved funds. Only the owner may cancel a round.
+    CancelRound { round_id: u64 },
+    /// Withdraws everything of `asset` credited to the sender by accrual distributions
+    Claim { asset: AssetInfo },
+    /// Entry point for CW20 tokens sent with `Cw20ExecuteMsg::Send`
+    Receive(Cw20ReceiveMsg),
+}
+
+/// Messages embedded in a `Cw20ReceiveMsg`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    /// Deposits the tokens into the contract balance for later distributions
+    Fund {},
+}
+
+/// How a distribution turns `amount` into per-recipient shares
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Split {
+    /// Every recipient gets `amount`
+    Fixed,
+    /// `amount` is a pool split evenly across the recipients. Shares are
+    /// rounded down; see `DustRule` for the remainder.
+    Even,
+    /// `amount` is a pool split in proportion to member weights. Shares are
+    /// rounded down; see `DustRule` for the remainder.
+    Weighted,
+}
+
+impl Default for Split {
+    fn default() -> Self {
+        Split::Fixed
+    }
+}
+
+impl fmt::Display for Split {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Split::Fixed => write!(f, "fixed"),
+            Split::Even => write!(f, "even"),
+            Split::Weighted => write!(f, "weighted"),
+        }
+    }
+}
+
+/// Where the rounding remainder of an even or weighted split goes
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DustRule {
+    /// Leave it in the contract
+    Retain,
+    /// Pay it to the contract owner, or leave it in the contract once
+    /// ownership has been renounced
+    Owner,
+    /// Add it to the share of the first whitelisted address
+    FirstMember,
+    /// Send it back to the caller. Only for `DistributeFunds`, where the
+    /// caller attached the funds being split.
+    Sender,
+}
+
+impl Default for DustRule {
+    fn default() -> Self {
+        DustRule::Retain
+    }
+}
+
+impl fmt::Display for DustRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DustRule::Retain => write!(f, "retain"),
+            DustRule::Owner => write!(f, "owner"),
+            DustRule::FirstMember => write!(f, "first_member"),
+            DustRule::Sender => write!(f, "sender"),
+        }
+    }
+}
+
+/// A permission the owner can grant to other addresses. The owner itself
+/// holds every role.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// May `Add`, `Remove` and `SetWhitelist`, and decide on applications
+    WhitelistManager,
+    /// May run every kind of distribution
+    Distributor,
+    /// May `Reset` the counter
+    CounterAdmin,
+    /// May `Pause` and `Unpause` the contract
+    Pauser,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [
+        Role::WhitelistManager,
+        Role::Distributor,
+        Role::CounterAdmin,
+        Role::Pauser,
+    ];
+
+    /// Storage key of the role, also used in events and errors
+    pub fn key(&self) -> &'static str {
+        match self {
+            Role::WhitelistManager => "whitelist_manager",
+            Role::Distributor => "distributor",
+            Role::CounterAdmin => "counter_admin",
+            Role::Pauser => "pauser",
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.key())
+    }
+}
+
+/// How a distribution hands funds to the recipients
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutMode {
+    /// Send each recipient their share in the same transaction
+    Push,
+    /// Credit each recipient in the claim ledger; they withdraw with `Claim`
+    Accrue,
+}
+
+impl Default for PayoutMode {
+    fn default() -> Self {
+        PayoutMode::Push
+    }
+}
+
+impl fmt::Display for PayoutMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PayoutMode::Push => write!(f, "push"),
+            PayoutMode::Accrue => write!(f, "accrue"),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -22,7 +328,86 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     // GetCount returns the current count as a json-encoded number
     GetCount {},
-    GetWhitelist {}
+    // Owner returns the current owner and any pending ownership transfer
+    Owner {},
+    // ListRoles returns the holders of every role, ordered by address
+    ListRoles {},
+    // Timelock returns the timelock configuration, if any
+    Timelock {},
+    // QueuedActions returns a page of queued actions ordered by id
+    QueuedActions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // Approvers returns who has to approve large distributions, if anyone
+    Approvers {},
+    // PendingDistributions returns a page of distributions waiting for approval, ordered by id
+    PendingDistributions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // PauseStatus returns whether the contract is paused, and who last paused or unpaused it
+    PauseStatus {},
+    // Membership queries read list `list`, or the default list when none is named
+    GetWhitelist { list: Option<String> },
+    // ListMembers returns a page of members ordered by address
+    ListMembers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        list: Option<String>,
+    },
+    // PendingApplications returns a page of open applications ordered by address
+    PendingApplications {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        list: Option<String>,
+    },
+    // IsMember returns whether `address` is on the whitelist
+    IsMember { address: String, list: Option<String> },
+    // MemberCount returns the number of members
+    MemberCount { list: Option<String> },
+    // MemberDetails returns the stored entry of `address`, expired or not
+    MemberDetails { address: String, list: Option<String> },
+    // IsMemberAtHeight returns whether `address` was a member at the start of block `height`
+    IsMemberAtHeight { address: String, height: u64, list: Option<String> },
+    // ListMembersAtHeight returns a page of the members at the start of block `height`
+    ListMembersAtHeight {
+        height: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        list: Option<String>,
+    },
+    // Lists returns a page of the whitelists ordered by name, with their member counts
+    Lists {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // The cw4 group queries. Expired members count as non-members in all of them.
+    // Member returns the weight of `addr`, now or at the start of block `at_height`
+    Member {
+        addr: String,
+        at_height: Option<u64>,
+        list: Option<String>,
+    },
+    // TotalWeight returns the summed weight of all members
+    TotalWeight { list: Option<String> },
+    // Admin returns the contract owner
+    Admin {},
+    // Hooks returns the contracts notified of membership changes
+    Hooks { list: Option<String> },
+    // Claimable returns everything credited to `address` and not yet claimed
+    Claimable { address: String },
+    // Shares returns every member's weight and fraction of a weighted distribution
+    Shares { list: Option<String> },
+    // Round returns the progress of a distribution round and its next pending payouts
+    Round { id: u64 },
+    // SimulateDistribution previews what `Distribute` would pay without executing it
+    SimulateDistribution {
+        amount: Uint128,
+        asset: AssetInfo,
+        split: Option<Split>,
+        list: Option<String>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -31,8 +416,157 @@ pub struct CountResponse {
     pub count: i32,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnerResponse {
+    pub owner: Option<String>,
+    pub pending_owner: Option<String>,
+    pub pending_expiry: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TimelockResponse {
+    pub timelock: Option<TimelockConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QueuedActionInfo {
+    pub id: u64,
+    pub action: QueuedAction,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QueuedActionsResponse {
+    pub actions: Vec<QueuedActionInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ApproversResponse {
+    pub config: Option<ApprovalConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingDistributionInfo {
+    pub id: u64,
+    pub distribution: PendingDistribution,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingDistributionsResponse {
+    pub distributions: Vec<PendingDistributionInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PauseStatusResponse {
+    pub paused: bool,
+    /// The last pause or unpause, if there ever was one
+    pub last_change: Option<PauseInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoleHolders {
+    pub role: Role,
+    pub addresses: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ListRolesResponse {
+    pub roles: Vec<RoleHolders>,
+}
+
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct WhitelistResponse {
     pub whitelist: Vec<String>,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsMemberResponse {
+    pub is_member: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MemberCountResponse {
+    pub count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ListSummary {
+    pub name: String,
+    /// Members that have not expired
+    pub member_count: u64,
+    /// The cw4 group distributions to the list pay, if any
+    pub group: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ListsResponse {
+    pub lists: Vec<ListSummary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingApplication {
+    pub address: String,
+    pub application: Application,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingApplicationsResponse {
+    pub applications: Vec<PendingApplication>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MemberDetailsResponse {
+    pub address: String,
+    pub member: Option<MemberInfo>,
+    pub expired: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimableResponse {
+    pub address: String,
+    pub claimable: Vec<Asset>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SharesResponse {
+    pub total_weight: u64,
+    pub shares: Vec<ShareInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ShareInfo {
+    pub address: String,
+    pub weight: u64,
+    pub share: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoundResponse {
+    pub id: u64,
+    pub mode: PayoutMode,
+    pub created_by: String,
+    pub recipients: u64,
+    pub processed: u64,
+    pub complete: bool,
+    pub cancelled: bool,
+    pub total: Vec<Asset>,
+    pub paid: Vec<Asset>,
+    /// The payouts the next `ContinueDistribution` would process
+    pub pending: Vec<Payout>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulationResponse {
+    pub asset: AssetInfo,
+    pub payouts: Vec<Payout>,
+    /// Amount the distribution needs from the contract
+    pub required: Uint128,
+    /// Rounding remainder that would stay in the contract
+    pub dust: Uint128,
+    /// Current contract balance of `asset`
+    pub balance: Uint128,
+    /// Part of `balance` not owed to claimants or open rounds
+    pub available: Uint128,
+    /// Whether `Distribute` would fail with `InsufficientError`
+    pub insufficient: bool,
+}
@@ -1,17 +1,326 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Addr;
-use cw_storage_plus::Item;
+use cosmwasm_std::{Addr, BlockInfo, Empty, StdResult, Timestamp, Uint128, Uint64};
+use cw_storage_plus::{Item, Map, SnapshotMap, Strategy};
+use cw_utils::Expiration;
+
+use crate::asset::Asset;
+use crate::msg::{DustRule, ExecuteMsg, PayoutMode, Split};
 
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub count: i32,
-    pub owner: Addr,
-    pub whitelist: Vec<String>,
+    /// `None` once ownership has been renounced
+    pub owner: Option<Addr>,
 }
 
 
 
 pub const STATE: Item<State> = Item::new("state");
+
+/// Granted roles, keyed by (`Role::key`, holder)
+pub const ROLES: Map<(&str, &Addr), Empty> = Map::new("roles");
+
+/// The last pause or unpause of the contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PauseInfo {
+    pub paused: bool,
+    pub set_by: Addr,
+    pub set_at_height: u64,
+    pub set_at_time: Timestamp,
+}
+
+/// Absent until the contract is paused for the first time
+pub const PAUSE: Item<PauseInfo> = Item::new("pause");
+
+/// Which actions have to wait in the queue, and for how long
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TimelockConfig {
+    /// Seconds a queued action waits before it can run
+    pub delay: u64,
+    /// Distributions needing more than this of any asset have to be queued
+    pub distribute_threshold: Uint128,
+    /// Removals of more than this many addresses at once have to be queued
+    pub bulk_remove_threshold: u32,
+}
+
+/// Absent while no timelock is configured
+pub const TIMELOCK: Item<TimelockConfig> = Item::new("timelock");
+
+/// An action waiting out the timelock
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QueuedAction {
+    pub msg: ExecuteMsg,
+    /// The action runs on behalf of this address
+    pub proposer: Addr,
+    pub queued_at: Timestamp,
+    pub ready_at: Timestamp,
+}
+
+pub const QUEUE: Map<u64, QueuedAction> = Map::new("queue");
+
+/// Id of the last queued action
+pub const QUEUE_COUNT: Item<u64> = Item::new("queue_count");
+
+/// Who has to sign off on large distributions
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ApprovalConfig {
+    pub approvers: Vec<Addr>,
+    /// Approvals needed before a distribution runs
+    pub required: u32,
+    /// Distributions needing more than this of any asset wait for approval
+    pub threshold: Uint128,
+}
+
+/// Absent while distributions need no approval
+pub const APPROVAL_CONFIG: Item<ApprovalConfig> = Item::new("approval_config");
+
+/// A distribution waiting for approval. It runs as planned by `proposer`
+/// once enough approvers have signed off.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingDistribution {
+    pub proposer: Addr,
+    pub assets: Vec<Asset>,
+    pub mode: PayoutMode,
+    pub split: Split,
+    pub dust: DustRule,
+    pub at_height: Option<u64>,
+    pub list: Option<String>,
+    pub approvals: Vec<Addr>,
+    /// Proposed through the timelock queue, so the delay was already waited
+    /// out. Otherwise the timelock is checked again when it runs.
+    #[serde(default)]
+    pub queued: bool,
+    /// Sweeps the whole free balance of its assets, worked out again when it
+    /// runs rather than taken from `assets`
+    #[serde(default)]
+    pub sweep: bool,
+}
+
+pub const PENDING_DISTRIBUTIONS: Map<u64, PendingDistribution> = Map::new("pending_distributions");
+
+/// Id of the last pending distribution
+pub const PENDING_DISTRIBUTION_COUNT: Item<u64> = Item::new("pending_distribution_count");
+
+/// An ownership transfer waiting for the new owner to accept it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwner {
+    pub address: Addr,
+    pub expiry: Expiration,
+}
+
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
+
+/// A distribution that is paid out in pages, possibly over several
+/// transactions. The membership it pays is fixed when it is created; every
+/// page reads the next members after `cursor` and works out their shares.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Round {
+    pub mode: PayoutMode,
+    pub created_by: Addr,
+    pub list_id: u64,
+    /// The cw4 group paid instead of the list's own members
+    pub group: Option<GroupSource>,
+    pub split: Split,
+    pub dust: DustRule,
+    /// With a fixed split the amount every member gets of each asset,
+    /// otherwise the pool of each asset that is split
+    pub assets: Vec<Asset>,
+    /// Members are paid as they stood at the start of this height
+    pub height: u64,
+    /// The block member expirations are checked against
+    pub block: BlockInfo,
+    /// Number of members the round pays
+    pub recipients: u64,
+    /// Their total weight
+    pub total_weight: u64,
+    /// Number of members already paid
+    pub processed: u64,
+    /// Last address looked at; the next page starts after it
+    pub cursor: Option<Addr>,
+    /// The first member paid, who gets the dust under `DustRule::FirstMember`
+    pub first_recipient: Option<Addr>,
+    /// Amount of each asset reserved for the round
+    pub total: Vec<Asset>,
+    /// Amount of each asset paid out or credited so far
+    pub paid: Vec<Asset>,
+    /// Set once every member has been paid and the dust handled
+    pub finished: bool,
+    /// Set when the owner abandoned the round before all payouts were made
+    pub cancelled: bool,
+}
+
+impl Round {
+    pub fn is_complete(&self) -> bool {
+        self.cancelled || self.finished
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Payout {
+    pub recipient: String,
+    pub assets: Vec<Asset>,
+}
+
+/// Id of the last created round
+pub const ROUND_COUNT: Item<u64> = Item::new("round_count");
+
+pub const ROUNDS: Map<u64, Round> = Map::new("rounds");
+
+/// Rounds not complete yet, keyed by (list id, round id), so that a list is
+/// not deleted while a round still reads its members
+pub const OPEN_ROUNDS: Map<(u64, u64), Empty> = Map::new("open_rounds");
+
+/// Weight used when a member was added without one
+pub const DEFAULT_WEIGHT: u64 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MemberInfo {
+    pub weight: u64,
+    pub label: Option<String>,
+    pub added_at_height: u64,
+    pub added_at_time: Timestamp,
+    pub added_by: Addr,
+    /// Expired members stay stored but are no longer paid or reported as members
+    pub expires: Expiration,
+}
+
+/// Name of the list used when a message names none
+pub const DEFAULT_LIST: &str = "default";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ListInfo {
+    pub name: String,
+    /// The cw4 group distributions to this list pay instead of its own members
+    #[serde(default)]
+    pub group: Option<GroupSource>,
+}
+
+/// An external cw4 group a list takes its recipients from
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GroupSource {
+    pub addr: Addr,
+    /// Whether weighted splits use the group's weights. Otherwise every
+    /// group member counts 1.
+    pub use_weights: bool,
+}
+
+/// Whitelists by id. Members are keyed by the id rather than the name, so a
+/// rename touches no members and keeps the list's snapshots.
+pub const LISTS: Map<u64, ListInfo> = Map::new("lists");
+
+/// List ids by name
+pub const LIST_IDS: Map<&str, u64> = Map::new("list_ids");
+
+/// Last list id handed out
+pub const LIST_COUNT: Item<u64> = Item::new("list_count");
+
+/// A number of members and their total weight
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Totals {
+    pub members: u64,
+    pub weight: u64,
+}
+
+impl Totals {
+    pub fn checked_add(&self, other: &Totals) -> StdResult<Totals> {
+        Ok(Totals {
+            members: Uint64::new(self.members).checked_add(Uint64::new(other.members))?.u64(),
+            weight: Uint64::new(self.weight).checked_add(Uint64::new(other.weight))?.u64(),
+        })
+    }
+
+    pub fn checked_sub(&self, other: &Totals) -> StdResult<Totals> {
+        Ok(Totals {
+            members: Uint64::new(self.members).checked_sub(Uint64::new(other.members))?.u64(),
+            weight: Uint64::new(self.weight).checked_sub(Uint64::new(other.weight))?.u64(),
+        })
+    }
+}
+
+/// The entries of each list in `MEMBERS`, expired ones included, checkpointed
+/// at every height they change. Absent until a list gets its first member.
+pub const LIST_TOTALS: SnapshotMap<u64, Totals> = SnapshotMap::new(
+    "list_totals",
+    "list_totals__checkpoints",
+    "list_totals__changelog",
+    Strategy::EveryBlock,
+);
+
+/// Members of each list, keyed by (list id, address) and checkpointed at
+/// every height they change so that past membership can be looked up
+pub const MEMBERS: SnapshotMap<(u64, &Addr), MemberInfo> = SnapshotMap::new(
+    "members",
+    "members__checkpoints",
+    "members__changelog",
+    Strategy::EveryBlock,
+);
+
+/// Every address that has ever been a member of a list, for listing past
+/// membership
+pub const MEMBER_HISTORY: Map<(u64, &Addr), Empty> = Map::new("member_history");
+
+/// Members of a list expiring at a block height, keyed by (list id, height).
+/// Entries are kept at zero rather than removed, so that the ones that
+/// mattered at a past height can still be found and read at that height.
+pub const EXPIRES_AT_HEIGHT: SnapshotMap<(u64, u64), Totals> = SnapshotMap::new(
+    "expires_at_height",
+    "expires_at_height__checkpoints",
+    "expires_at_height__changelog",
+    Strategy::EveryBlock,
+);
+
+/// Members of a list expiring at a block time, keyed by (list id,
+/// nanoseconds). Kept like `EXPIRES_AT_HEIGHT`.
+pub const EXPIRES_AT_TIME: SnapshotMap<(u64, u64), Totals> = SnapshotMap::new(
+    "expires_at_time",
+    "expires_at_time__checkpoints",
+    "expires_at_time__changelog",
+    Strategy::EveryBlock,
+);
+
+/// A request to join a list, waiting for the owner to approve or reject it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Application {
+    pub memo: Option<String>,
+    pub applied_at_height: u64,
+    pub applied_at_time: Timestamp,
+}
+
+/// Pending applications, keyed by (list id, applicant)
+pub const APPLICATIONS: Map<(u64, &Addr), Application> = Map::new("applications");
+
+/// Contracts sent a cw4 `MemberChangedHookMsg` when a list changes, keyed by
+/// (list id, hook address)
+pub const HOOKS: Map<(u64, &Addr), Empty> = Map::new("hooks");
+
+/// Amounts credited by accrual distributions and not yet claimed, by
+/// (recipient, `AssetInfo::key`)
+pub const CLAIMABLE: Map<(&Addr, &str), Uint128> = Map::new("claimable");
+
+/// Per-asset total of `CLAIMABLE` and of the unpaid part of open rounds. This
+/// part of the contract balance is owed to recipients and must not be spent by
+/// another distribution.
+pub const RESERVED: Map<&str, Uint128> = Map::new("reserved");
+
+/// Storage layout of earlier versions, only read by `migrate`
+pub mod legacy {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct State {
+        pub count: i32,
+        /// Stored as an address before ownership could be renounced
+        pub owner: Option<Addr>,
+        /// Absent once migrated
+        #[serde(default)]
+        pub whitelist: Vec<String>,
+    }
+
+    pub const STATE: Item<State> = Item::new("state");
+
+    /// Distribution weight per whitelisted address, keyed by the unchecked address
+    pub const WEIGHTS: Map<&str, u64> = Map::new("weights");
+}